@@ -1,431 +1,240 @@
-use num::Zero;
+use itertools::Itertools;
+use serde_json::{Map, Number, Value};
 
 use crate::public::{
-    parse_json_string::parse_json_string,
-    parse_stringified_json_string::parse_stringified_json_string,
+    lenient_value::{LenientKind, LenientMember, LenientValue},
+    prepare_error::{PrepareError, PrepareErrorKind},
+    prepare_options::ValueMode,
+    repair::{Repair, RepairKind},
 };
 
-pub(crate) fn json_context(trimmed_str: &str) -> JsonContext {
-    let json_context = match trimmed_str {
-        trimmed_str if trimmed_str.starts_with('{') && trimmed_str.ends_with('}') => {
-            JsonContext::Object
-        }
-        trimmed_str if trimmed_str.starts_with('[') && trimmed_str.ends_with(']') => {
-            JsonContext::Array
-        }
-        _ => JsonContext::Value,
-    };
-
-    json_context
-}
-
-pub(crate) fn ensure_array_wrapper(string: &str) -> String {
-    let array_string = if string.starts_with('[') && string.ends_with(']') {
-        string.to_string()
-    } else {
-        format!("[{string}]")
-    };
-    array_string
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum JsonContext {
+    Array,
+    Object,
+    Value,
 }
 
-#[allow(clippy::needless_pass_by_value)]
-pub(crate) fn content_str(json_context: JsonContext, trimmed_str: &str) -> String {
-    let content_str = match json_context {
-        JsonContext::Array | JsonContext::Object => {
-            let mut content_str = trimmed_str[1..].to_string();
-            content_str.pop();
-
-            let trimmed_content_str = content_str
-                .trim_matches([' ', '\n', '\t', ',', ';', ':'])
-                .trim_start_matches("\\n")
-                .trim_end_matches("\\n")
-                .trim_start_matches("\\\\n")
-                .trim_end_matches("\\\\n")
-                .to_string();
-
-            trimmed_content_str
+pub(crate) fn ensure_array_wrapper(lenient_value: LenientValue) -> LenientValue {
+    let array_value = match lenient_value.kind {
+        LenientKind::Array(mut elements)
+            if elements.len() == 1 && matches!(elements[0].kind, LenientKind::Array(_)) =>
+        {
+            elements.remove(0)
+        }
+        LenientKind::Array(_) => lenient_value,
+        _ => {
+            let span = lenient_value.span;
+            LenientValue::new(LenientKind::Array(vec![lenient_value]), span)
         }
-        JsonContext::Value => trimmed_str.to_string(),
     };
 
-    content_str
+    array_value
 }
 
-#[allow(clippy::needless_pass_by_value)]
-pub(crate) fn rewrap_string(parsed_json_string: &str, json_context: JsonContext) -> String {
-    let rewrapped_string = match json_context {
-        JsonContext::Array => {
-            let rewrapped_string = format!("[{parsed_json_string}]");
+pub(crate) fn render_value(lenient_value: &LenientValue, value_mode: ValueMode) -> String {
+    let rendered_value = match &lenient_value.kind {
+        LenientKind::Object(members) => render_object(members, value_mode),
+        LenientKind::Array(elements) => {
+            let array_elements = elements
+                .iter()
+                .map(|element| render_value(element, value_mode))
+                .join(", ");
 
-            rewrapped_string
+            format!("[{array_elements}]")
         }
-        JsonContext::Object => {
-            let rewrapped_string = format!("{{{parsed_json_string}}}");
-
-            rewrapped_string
+        LenientKind::String(string) | LenientKind::Bare(string) => quote(string),
+        LenientKind::Number(number) if value_mode == ValueMode::Stringified => quote(number),
+        LenientKind::Bool(boolean) if value_mode == ValueMode::Stringified => {
+            quote(&boolean.to_string())
         }
-        JsonContext::Value => parsed_json_string.to_string(),
+        LenientKind::Null if value_mode == ValueMode::Stringified => quote("null"),
+        LenientKind::Empty if value_mode == ValueMode::Stringified => quote(""),
+        LenientKind::Number(number) => normalize_number(number),
+        LenientKind::Bool(boolean) => boolean.to_string(),
+        LenientKind::Null | LenientKind::Empty => "null".to_string(),
     };
 
-    rewrapped_string
-}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub(crate) enum JsonContext {
-    Array,
-    Object,
-    Value,
-}
-
-pub(crate) fn handle_object_w_wrapper(string: &str) -> String {
-    let mut content_string = string[1..].to_string();
-    content_string.pop();
-    let content_string = content_string
-        .trim_matches([' ', '\n', '\t', ','])
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .trim_start_matches("\\n")
-        .trim_end_matches("\\n");
-
-    let object_context = JsonContext::Object;
-
-    let new_object_substance = parse_json_string(content_string, object_context);
-
-    let new_with_braces = format!("{{{new_object_substance}}}");
-
-    new_with_braces
+    rendered_value
 }
 
-pub(crate) fn handle_stringified_object_w_wrapper(string: &str) -> String {
-    let mut content_string = string[1..].to_string();
-    content_string.pop();
-    let content_string = content_string
-        .trim_matches([' ', '\n', '\t', ','])
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .trim_start_matches("\\n")
-        .trim_end_matches("\\n");
+/// Rewrites the number spellings the parser accepts but JSON does not, such as `+1`, `.5` and
+/// `5.`. A trailing decimal point keeps the number fractional, so `5.` becomes `5.0`.
+fn normalize_number(number: &str) -> String {
+    let lowercase = number.to_lowercase();
 
-    let object_context = JsonContext::Object;
-
-    let new_object_substance = parse_stringified_json_string(content_string, object_context);
-
-    let new_with_braces = format!("{{{new_object_substance}}}");
-
-    new_with_braces
-}
-
-pub(crate) fn handle_stringified_array_content(string: &str) -> String {
-    let mut array_elements = split_array_elements(string)
-        .iter()
-        .map(|element| {
-            let value_context = JsonContext::Value;
-
-            let new_element = parse_stringified_json_string(element, value_context);
-            let formatted_element = format!("{new_element}, ");
-            formatted_element
-        })
-        .collect::<String>();
-    array_elements.pop();
-    array_elements.pop();
-
-    array_elements
-}
-
-pub(crate) fn handle_array_content(string: &str) -> String {
-    let mut array_elements = split_array_elements(string)
-        .iter()
-        .map(|element| {
-            let value_context = JsonContext::Value;
-
-            let new_element = parse_json_string(element, value_context);
-            let formatted_element = format!("{new_element}, ");
-            formatted_element
-        })
-        .collect::<String>();
-    array_elements.pop();
-    array_elements.pop();
-
-    array_elements
-}
-
-pub(crate) fn handle_stringified_array_w_wrapper(string: &str) -> String {
-    let mut content_str = string[1..].to_string();
-    content_str.pop();
-    let content_str = content_str.trim();
-
-    let mut array_str = split_array_elements(content_str)
-        .iter()
-        .map(|element| {
-            let value_context = JsonContext::Value;
-
-            let new_element = parse_stringified_json_string(element, value_context);
-            let formatted_element = format!("{new_element}, ");
-            formatted_element
-        })
-        .collect::<String>();
-    array_str.pop();
-    array_str.pop();
-
-    let add_the_brackets_back = format!("[{array_str}]");
-
-    add_the_brackets_back
-}
-
-pub(crate) fn handle_array_w_wrapper(string: &str) -> String {
-    let mut content_str = string[1..].to_string();
-    content_str.pop();
-    let content_str = content_str.trim();
-
-    let mut array_str = split_array_elements(content_str)
-        .iter()
-        .map(|element| {
-            let value_context = JsonContext::Value;
-
-            let new_element = parse_json_string(element, value_context);
-            let formatted_element = format!("{new_element}, ");
-            formatted_element
-        })
-        .collect::<String>();
-    array_str.pop();
-    array_str.pop();
-
-    let add_the_brackets_back = format!("[{array_str}]");
-
-    add_the_brackets_back
-}
-
-pub(crate) fn handle_stringified_object_content(string: &str) -> String {
-    let mut key_value_pairs = split_object_elements(string)
-        .iter()
-        .filter_map(|kv_pair| {
-            let (key, value) = kv_pair.split_once(':')?;
-
-            let trimmed_key = key.trim();
-
-            let new_key = if trimmed_key.starts_with('\"') && trimmed_key.ends_with('\"') {
-                trimmed_key.to_string()
-            } else {
-                format!("\"{trimmed_key}\"")
-            };
-
-            let trimmed_value = value
-                .trim_matches([' ', '\n', '\t', ','])
-                .trim_start_matches("\\\\n")
-                .trim_end_matches("\\\\n")
-                .trim_start_matches("\\n")
-                .trim_end_matches("\\n");
-
-            let value_context = JsonContext::Value;
-
-            let new_value = parse_stringified_json_string(trimmed_value, value_context);
-
-            let new_kv_pair = format!("{new_key}: {new_value}, ");
-
-            Some(new_kv_pair)
-        })
-        .collect::<String>();
-    key_value_pairs.pop();
-    key_value_pairs.pop();
+    let (sign, unsigned) = match lowercase.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", lowercase.strip_prefix('+').unwrap_or(&lowercase)),
+    };
+    let (mantissa, exponent) = match unsigned.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, format!("e{exponent}")),
+        None => (unsigned, String::new()),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, "")) => (integer, ".0".to_string()),
+        Some((integer, fraction)) => (integer, format!(".{fraction}")),
+        None => (mantissa, String::new()),
+    };
+    let integer = if integer.is_empty() { "0" } else { integer };
 
-    key_value_pairs
+    format!("{sign}{integer}{fraction}{exponent}")
 }
 
-pub(crate) fn handle_object_content(string: &str) -> String {
-    let mut key_value_pairs = split_object_elements(string)
+fn render_object(members: &[LenientMember], value_mode: ValueMode) -> String {
+    let key_value_pairs = members
         .iter()
-        .filter_map(|kv_pair| {
-            let (key, value) = kv_pair.split_once(':')?;
-
-            let trimmed_key = key.trim();
-
-            let new_key = if trimmed_key.starts_with('\"') && trimmed_key.ends_with('\"') {
-                trimmed_key.to_string()
-            } else {
-                format!("\"{trimmed_key}\"")
-            };
-
-            let trimmed_value = value
-                .trim_matches([' ', '\n', '\t', ','])
-                .trim_start_matches("\\\\n")
-                .trim_end_matches("\\\\n")
-                .trim_start_matches("\\n")
-                .trim_end_matches("\\n");
-
-            let value_context = JsonContext::Value;
-
-            let new_value = parse_json_string(trimmed_value, value_context);
-
-            let new_kv_pair = format!("{new_key}: {new_value}, ");
+        .filter_map(|member| {
+            let key = member.key.as_ref()?;
+            let new_key = quote(&key.name);
+            let new_value = render_value(&member.value, value_mode);
 
-            Some(new_kv_pair)
+            Some(format!("{new_key}: {new_value}"))
         })
-        .collect::<String>();
-    key_value_pairs.pop();
-    key_value_pairs.pop();
-
-    key_value_pairs
-}
-
-pub(crate) fn split_array_elements(string: &str) -> Vec<String> {
-    let mut all_elements = Vec::new();
-    let mut current_element = String::default();
-    let mut array_lefts = 0;
-    let mut object_lefts = 0;
-
-    for ch in string.chars() {
-        let is_separator = ch == ',' || ch == ';';
-
-        if is_separator && array_lefts.is_zero() && object_lefts.is_zero() {
-            let trimmed_current_element = current_element
-                .trim_matches([' ', '\n', '\t', ',', ';'])
-                .trim_start_matches("\\\\n")
-                .trim_end_matches("\\\\n")
-                .trim_start_matches("\\n")
-                .trim_end_matches("\\n")
-                .to_string();
-            all_elements.push(trimmed_current_element.clone());
-            current_element.clear();
-        }
-
-        if ch == '{' && array_lefts.is_zero() {
-            object_lefts += 1;
-        }
-
-        if ch == '[' && object_lefts.is_zero() {
-            array_lefts += 1;
-        }
-
-        if ch == ']' && !array_lefts.is_zero() {
-            array_lefts -= 1;
-        }
-
-        if ch == '}' && !object_lefts.is_zero() {
-            object_lefts -= 1;
+        .join(", ");
+
+    format!("{{{key_value_pairs}}}")
+}
+
+/// Collects the repairs made while rendering scalars: quoting bare text, normalizing the
+/// spelling of literals, and turning leaves into strings in stringified mode.
+pub(crate) fn value_repairs(
+    lenient_value: &LenientValue,
+    value_mode: ValueMode,
+    input: &str,
+    repairs: &mut Vec<Repair>,
+) {
+    let raw = &input[lenient_value.span.start..lenient_value.span.end];
+
+    match &lenient_value.kind {
+        LenientKind::Object(members) => {
+            let rendered_members = members.iter().filter(|member| member.key.is_some());
+            for member in rendered_members {
+                value_repairs(&member.value, value_mode, input, repairs);
+            }
+        }
+        LenientKind::Array(elements) => {
+            for element in elements {
+                value_repairs(element, value_mode, input, repairs);
+            }
+        }
+        LenientKind::String(_) | LenientKind::Empty => {}
+        LenientKind::Bare(_) => repairs.push(Repair::new(
+            RepairKind::QuotedValue,
+            lenient_value.span,
+            format!("quoted `{raw}`"),
+        )),
+        LenientKind::Number(_) | LenientKind::Bool(_) | LenientKind::Null
+            if value_mode == ValueMode::Stringified =>
+        {
+            repairs.push(Repair::new(
+                RepairKind::StringifiedValue,
+                lenient_value.span,
+                format!("turned `{raw}` into a string"),
+            ));
+        }
+        LenientKind::Number(_) | LenientKind::Bool(_) | LenientKind::Null => {
+            let rendered_value = render_value(lenient_value, value_mode);
+
+            if rendered_value != raw {
+                repairs.push(Repair::new(
+                    RepairKind::NormalizedLiteral,
+                    lenient_value.span,
+                    format!("rewrote `{raw}` as `{rendered_value}`"),
+                ));
+            }
         }
-        current_element.push(ch);
     }
-
-    let trimmed_current_element = current_element
-        .trim_matches([' ', '\n', '\t', ',', ';'])
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .trim_start_matches("\\n")
-        .trim_end_matches("\\n")
-        .to_string();
-    all_elements.push(trimmed_current_element.clone());
-    current_element.clear();
-
-    all_elements
 }
 
-pub(crate) fn split_object_elements(object_str: &str) -> Vec<String> {
-    let mut all_elements = Vec::new();
-    let mut current_element = String::default();
-    let mut array_lefts = 0;
-    let mut object_lefts = 0;
-    let mut double_quote_lefts = 0;
+pub(crate) fn to_json_value(
+    lenient_value: &LenientValue,
+    value_mode: ValueMode,
+    input: &str,
+) -> Result<Value, PrepareError> {
+    let invalid_value = || {
+        PrepareError::new(
+            PrepareErrorKind::InvalidValue,
+            input,
+            lenient_value.span.start,
+        )
+    };
 
-    for ch in object_str.chars() {
-        let is_separator = ch == ',' || ch == ';';
-        if is_separator
-            && array_lefts.is_zero()
-            && object_lefts.is_zero()
-            && double_quote_lefts.is_zero()
+    let json_value = match &lenient_value.kind {
+        LenientKind::Object(members) => {
+            let mut object = Map::with_capacity(members.len());
+            for member in members {
+                let Some(key) = &member.key else {
+                    continue;
+                };
+                let value = to_json_value(&member.value, value_mode, input)?;
+                object.insert(key.name.clone(), value);
+            }
+
+            Value::Object(object)
+        }
+        LenientKind::Array(elements) => {
+            let array = elements
+                .iter()
+                .map(|element| to_json_value(element, value_mode, input))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Value::Array(array)
+        }
+        LenientKind::String(string) | LenientKind::Bare(string)
+            if value_mode == ValueMode::Stringified =>
         {
-            let trimmed_element = current_element
-                .trim_matches([' ', '\n', '\t', ','])
-                .trim_start_matches("\\\\n")
-                .trim_end_matches("\\\\n")
-                .trim_start_matches("\\n")
-                .trim_end_matches("\\n")
-                .to_string();
-            all_elements.push(trimmed_element);
-            current_element.clear();
+            Value::String(string.clone())
         }
-
-        if ch == '{' && array_lefts.is_zero() && double_quote_lefts.is_zero() {
-            object_lefts += 1;
+        LenientKind::Number(number) if value_mode == ValueMode::Stringified => {
+            Value::String(number.clone())
         }
-
-        if ch == '[' && object_lefts.is_zero() && double_quote_lefts.is_zero() {
-            array_lefts += 1;
+        LenientKind::Bool(boolean) if value_mode == ValueMode::Stringified => {
+            Value::String(boolean.to_string())
         }
-
-        if ch == '\"' && object_lefts.is_zero() && array_lefts.is_zero() {
-            if double_quote_lefts.is_zero() {
-                double_quote_lefts += 1;
-            } else {
-                double_quote_lefts -= 1;
-            };
+        LenientKind::Null if value_mode == ValueMode::Stringified => {
+            Value::String("null".to_string())
         }
-
-        if ch == ']' && !array_lefts.is_zero() && double_quote_lefts.is_zero() {
-            array_lefts -= 1;
+        LenientKind::Empty if value_mode == ValueMode::Stringified => Value::String(String::new()),
+        LenientKind::String(string) => Value::String(string.clone()),
+        LenientKind::Number(number) => {
+            Value::Number(to_json_number(number).ok_or_else(invalid_value)?)
         }
+        LenientKind::Bool(boolean) => Value::Bool(*boolean),
+        LenientKind::Null | LenientKind::Empty => Value::Null,
+        LenientKind::Bare(bare) => Value::String(bare.clone()),
+    };
 
-        if ch == '}' && !object_lefts.is_zero() && double_quote_lefts.is_zero() {
-            object_lefts -= 1;
-        }
+    Ok(json_value)
+}
 
-        current_element.push(ch);
-    }
+fn to_json_number(number: &str) -> Option<Number> {
+    let lowercase = number.to_lowercase();
 
-    let trimmed_element = current_element
-        .trim_matches([' ', '\n', '\t', ','])
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .trim_start_matches("\\n")
-        .trim_end_matches("\\n")
-        .to_string();
-    all_elements.push(trimmed_element);
-    current_element.clear();
+    let json_number = if let Ok(unsigned) = lowercase.parse::<u64>() {
+        Some(Number::from(unsigned))
+    } else if let Ok(signed) = lowercase.parse::<i64>() {
+        Some(Number::from(signed))
+    } else {
+        lowercase.parse::<f64>().ok().and_then(Number::from_f64)
+    };
 
-    all_elements
+    json_number
 }
 
-pub(crate) fn format_stringified_value(value_str: &str) -> String {
-    if value_str.is_empty() {
-        return String::default();
-    }
-
-    let without_quotes = value_str
-        .trim_matches('\"')
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .trim_start_matches("\\n")
-        .trim_end_matches("\\n");
-    let formatted_value = format!("\"{without_quotes}\"");
-
-    formatted_value
+pub(crate) fn is_strict_json(string: &str) -> bool {
+    serde_json::from_str::<serde::de::IgnoredAny>(string).is_ok()
 }
 
-pub(crate) fn format_value(value_str: &str) -> String {
-    let formatted_value = match value_str.to_lowercase().as_str() {
-        value_str
-            if value_str.parse::<bool>().is_ok()
-                || value_str.parse::<f64>().is_ok()
-                || value_str.parse::<u64>().is_ok()
-                || value_str.parse::<i64>().is_ok()
-                || value_str.starts_with('[') && value_str.ends_with(']')
-                || value_str.starts_with('{') && value_str.ends_with('}')
-                || value_str.is_empty() =>
-        {
-            value_str.to_string()
-        }
-        "none" => "None".to_string(),
-        _ => {
-            let without_quotes = value_str
-                .trim_matches('\"')
-                .trim_start_matches("\\\\n")
-                .trim_end_matches("\\\\n")
-                .trim_start_matches("\\n")
-                .trim_end_matches("\\n");
+pub(crate) fn line_column(input: &str, offset: usize) -> (usize, usize) {
+    let before_offset = &input[..offset];
+    let line = before_offset.matches('\n').count() + 1;
+    let line_start = before_offset.rfind('\n').map_or(0, |newline| newline + 1);
+    let column = before_offset[line_start..].chars().count() + 1;
 
-            let with_quotes = format!("\"{without_quotes}\"");
-            with_quotes
-        }
-    };
+    (line, column)
+}
 
-    formatted_value
+pub(crate) fn quote(string: &str) -> String {
+    serde_json::Value::String(string.to_string()).to_string()
 }
@@ -0,0 +1,1039 @@
+use std::borrow::Cow;
+
+use itertools::Itertools;
+
+use crate::{
+    public::{
+        lenient_value::Span,
+        prepare_options::{CommentStyle, PrepareOptions},
+    },
+    syntax::{Escapes, Syntax},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    LeftParen,
+    RightParen,
+    Colon,
+    Separator,
+    String,
+    UnterminatedString,
+    Text,
+    EscapedNewline,
+    Comment,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) span: Span,
+}
+
+impl Token {
+    pub(crate) fn is_separator(&self) -> bool {
+        self.kind == TokenKind::Separator
+    }
+
+    pub(crate) fn is_opener(&self) -> bool {
+        matches!(
+            self.kind,
+            TokenKind::LeftBrace | TokenKind::LeftBracket | TokenKind::LeftParen
+        )
+    }
+
+    pub(crate) fn is_closer(&self) -> bool {
+        matches!(
+            self.kind,
+            TokenKind::RightBrace | TokenKind::RightBracket | TokenKind::RightParen
+        )
+    }
+
+    pub(crate) fn is_string(&self) -> bool {
+        matches!(self.kind, TokenKind::String | TokenKind::UnterminatedString)
+    }
+}
+
+pub(crate) fn tokenize(input: &str, options: &PrepareOptions) -> Vec<Token> {
+    let bytes = input.as_bytes();
+    let syntax = options.dialect.syntax();
+    let mut tokens = Vec::new();
+    let mut open_brackets = Vec::new();
+    let mut keyed_bodies = Vec::new();
+    let mut equals_ahead = None;
+    let mut position = 0;
+
+    while position < bytes.len() {
+        let start = position;
+
+        let escaped_newline_len = escaped_newline_len(&bytes[position..], options);
+        if escaped_newline_len > 0 {
+            position += escaped_newline_len;
+            tokens.push(Token {
+                kind: TokenKind::EscapedNewline,
+                span: Span::new(start, position),
+            });
+            continue;
+        }
+
+        // A line break after a key, or before the `(` of a PHP `Array`, does not separate.
+        let after_colon = previous_kind(&tokens) == Some(TokenKind::Colon);
+        let before_paren = syntax.parens && input[position..].trim_start().starts_with('(');
+        let whitespace_len = whitespace_len(input, position, &syntax);
+        if whitespace_len > 0 {
+            position += whitespace_len;
+            let separates = (syntax.newline_separators
+                && bytes[start] == b'\n'
+                && !after_colon
+                && !before_paren)
+                || (syntax.whitespace_separators
+                    && whitespace_separates(input, position, &tokens, &keyed_bodies, options));
+            if separates {
+                tokens.push(Token {
+                    kind: TokenKind::Separator,
+                    span: Span::new(start, position),
+                });
+            }
+            continue;
+        }
+
+        let comment_len = comment_len(bytes, position, options, &syntax);
+        if comment_len > 0 {
+            position += comment_len;
+            tokens.push(Token {
+                kind: TokenKind::Comment,
+                span: Span::new(start, position),
+            });
+            continue;
+        }
+
+        let dialect_tokens =
+            dialect_tokens(input, position, after_colon, &mut equals_ahead, options);
+        if let Some((dialect_tokens, end)) = dialect_tokens {
+            tokens.extend(dialect_tokens);
+            position = end;
+            continue;
+        }
+
+        let is_pointer = syntax.go_values
+            && bytes[position] == b'&'
+            && matches!(bytes.get(position + 1), Some(b'{' | b'['));
+        if is_pointer || (syntax.symbols && is_symbol_start(bytes, position, options)) {
+            position += 1;
+            continue;
+        }
+
+        let quoteless_end =
+            if syntax.quoteless_strings && is_value_position(&tokens, &open_brackets) {
+                quoteless_end(input, position, options, &syntax, !open_brackets.is_empty())
+            } else {
+                None
+            };
+
+        let (kind, end) = match bytes[position] {
+            _ if quoteless_end.is_some() => (TokenKind::Text, quoteless_end.unwrap_or(position)),
+            _ if syntax.multiline_strings && bytes[position..].starts_with(b"'''") => {
+                let close = input[position + 3..].find("'''");
+                let end = close.map_or(bytes.len(), |close| position + close + 6);
+                (string_kind(close.is_some()), end)
+            }
+            b'{' => (TokenKind::LeftBrace, position + 1),
+            b'}' => (TokenKind::RightBrace, position + 1),
+            b'[' => (TokenKind::LeftBracket, position + 1),
+            b']' => (TokenKind::RightBracket, position + 1),
+            byte if syntax.is_pair_separator(byte) => (TokenKind::Colon, position + 1),
+            b'=' if syntax.fat_arrows && bytes[position..].starts_with(b"=>") => {
+                (TokenKind::Colon, position + 2)
+            }
+            b'(' if syntax.parens => (TokenKind::LeftParen, position + 1),
+            b')' if syntax.parens => (TokenKind::RightParen, position + 1),
+            byte if syntax.is_quote(byte) || syntax.string_prefix_len(&bytes[position..]) > 0 => {
+                let quote_position = position + syntax.string_prefix_len(&bytes[position..]);
+                let (end, terminated) = string_end(bytes, quote_position);
+                (string_kind(terminated), end)
+            }
+            byte if options.is_separator(byte) => (TokenKind::Separator, position + 1),
+            _ => (TokenKind::Text, text_end(input, position, options, &syntax)),
+        };
+
+        track_brackets(kind, &mut open_brackets, &mut keyed_bodies);
+
+        position = end;
+        tokens.push(Token {
+            kind,
+            span: Span::new(start, position),
+        });
+    }
+
+    tokens
+}
+
+/// Keeps the stack of open brackets, and whether each body holds `key: value` pairs so far,
+/// up to date with the token just lexed.
+fn track_brackets(
+    kind: TokenKind,
+    open_brackets: &mut Vec<TokenKind>,
+    keyed_bodies: &mut Vec<bool>,
+) {
+    match kind {
+        TokenKind::LeftBrace | TokenKind::LeftBracket => {
+            open_brackets.push(kind);
+            keyed_bodies.push(false);
+        }
+        TokenKind::RightBrace | TokenKind::RightBracket => {
+            open_brackets.pop();
+            keyed_bodies.pop();
+        }
+        TokenKind::Colon => {
+            if let Some(keyed) = keyed_bodies.last_mut() {
+                *keyed = true;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A member at the top level of an array body, as found by [`split_array_body`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct BodyMember {
+    pub(crate) span: Span,
+    /// The separator after the member, or `None` for the last member, which ends at the closer
+    /// or at the end of the input.
+    pub(crate) separator: Option<Span>,
+    /// The first `:` or `=>` at the member's own level, if it has a key.
+    pub(crate) colon: Option<Span>,
+}
+
+/// The members of an array body and the bracket closing it, if the body is closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ArrayBody {
+    pub(crate) members: Vec<BodyMember>,
+    pub(crate) closer: Option<Span>,
+}
+
+/// Splits an array body into its top-level members with the tokens [`tokenize`] finds, so
+/// input can be cut at the same separators a parse of the whole of it sees. With
+/// `in_brackets`, `body` follows the array's `[` and a `]` that closes nothing opened in it
+/// ends the array. Otherwise `body` is a bare list of elements.
+pub(crate) fn split_array_body(
+    body: &str,
+    in_brackets: bool,
+    options: &PrepareOptions,
+) -> ArrayBody {
+    let prefix_len = usize::from(in_brackets);
+    let input = if in_brackets {
+        Cow::Owned(format!("[{body}"))
+    } else {
+        Cow::Borrowed(body)
+    };
+    let body_span = |span: Span| Span::new(span.start - prefix_len, span.end - prefix_len);
+
+    let mut members = Vec::new();
+    let mut open_closers = Vec::new();
+    let mut member_start = 0;
+    let mut colon = None;
+    let mut closer = None;
+
+    for token in tokenize(&input, options).into_iter().skip(prefix_len) {
+        match token.kind {
+            TokenKind::LeftBrace => open_closers.push(TokenKind::RightBrace),
+            TokenKind::LeftBracket => open_closers.push(TokenKind::RightBracket),
+            TokenKind::LeftParen => open_closers.push(TokenKind::RightParen),
+            kind if token.is_closer() => {
+                match open_closers
+                    .iter()
+                    .rposition(|&open_closer| open_closer == kind)
+                {
+                    Some(open_index) => open_closers.truncate(open_index),
+                    None if in_brackets && kind == TokenKind::RightBracket => {
+                        closer = Some(body_span(token.span));
+                        break;
+                    }
+                    None => {}
+                }
+            }
+            TokenKind::Colon if open_closers.is_empty() => {
+                colon = colon.or(Some(body_span(token.span)));
+            }
+            TokenKind::Separator if open_closers.is_empty() => {
+                let separator = body_span(token.span);
+                members.push(BodyMember {
+                    span: Span::new(member_start, separator.start),
+                    separator: Some(separator),
+                    colon: colon.take(),
+                });
+                member_start = separator.end;
+            }
+            _ => {}
+        }
+    }
+
+    let end = closer.map_or(body.len(), |closer| closer.start);
+    members.push(BodyMember {
+        span: Span::new(member_start, end),
+        separator: None,
+        colon,
+    });
+
+    ArrayBody { members, closer }
+}
+
+fn previous_kind(tokens: &[Token]) -> Option<TokenKind> {
+    let previous = tokens
+        .iter()
+        .rev()
+        .find(|token| !matches!(token.kind, TokenKind::Comment | TokenKind::EscapedNewline));
+
+    previous.map(|token| token.kind)
+}
+
+/// Lexes the parts of PHP `var_export` and `print_r` output that the generic rules would split
+/// apart, returning their tokens and where they end:
+///
+/// - the `(object)` cast before stdClass arrays, which is dropped,
+/// - the `\Name::__set_state` call wrapping exported objects,
+/// - the `Name Object` header of a `print_r` object,
+/// - a `print_r` member such as `[key] => some text`, whose value runs to the end of the line,
+///   the next member or the `)` closing its array.
+fn php_tokens(input: &str, position: usize) -> Option<(Vec<Token>, usize)> {
+    let rest = &input[position..];
+    let is_name_char = |ch: char| ch.is_alphanumeric() || matches!(ch, '_' | '\\');
+    let text_token = |start: usize, end: usize| Token {
+        kind: TokenKind::Text,
+        span: Span::new(start, end),
+    };
+
+    if rest.starts_with("(object)") {
+        return Some((Vec::new(), position + "(object)".len()));
+    }
+
+    let name_len = rest
+        .find(|ch: char| !is_name_char(ch))
+        .unwrap_or(rest.len());
+    if name_len > 0 {
+        let after_name = &rest[name_len..];
+
+        if let Some(method) = after_name.strip_prefix("::") {
+            let method_len = method
+                .find(|ch: char| !is_name_char(ch))
+                .unwrap_or(method.len());
+            if method_len > 0 && method[method_len..].starts_with('(') {
+                let end = position + name_len + 2 + method_len;
+                return Some((vec![text_token(position, end)], end));
+            }
+        }
+
+        let after_header = after_name.strip_prefix(" Object")?.trim_start();
+        if after_header.is_empty() || after_header.starts_with('(') {
+            let end = position + name_len + " Object".len();
+            return Some((vec![text_token(position, end)], end));
+        }
+
+        return None;
+    }
+
+    let (key_len, value) = print_r_member(rest)?;
+    let key_start = position + 1;
+    let arrow_start = input.len() - value.len() - 2;
+    let value_start = input.len() - value.trim_start_matches([' ', '\t']).len();
+    let value_end = print_r_value_end(input, value_start);
+    let trimmed_end = value_start + input[value_start..value_end].trim_end().len();
+
+    // Members on one line are not split by a line break, so each starts with an empty
+    // separator, which is dropped like a line break when it is not needed.
+    let mut tokens = vec![
+        Token {
+            kind: TokenKind::Separator,
+            span: Span::new(position, position),
+        },
+        text_token(key_start, key_start + key_len),
+        Token {
+            kind: TokenKind::Colon,
+            span: Span::new(arrow_start, arrow_start + 2),
+        },
+    ];
+    if value_start < trimmed_end {
+        tokens.push(text_token(value_start, trimmed_end));
+    }
+
+    Some((tokens, value_end))
+}
+
+/// Splits a `print_r` member starting with `[key] =>` into the length of its key and the
+/// input after the `=>`.
+fn print_r_member(rest: &str) -> Option<(usize, &str)> {
+    let key = rest.strip_prefix('[')?;
+    let key_len = key.find([']', '[', '\n'])?;
+    let after_key = key[key_len..].strip_prefix(']')?;
+    let value = after_key
+        .trim_start_matches([' ', '\t'])
+        .strip_prefix("=>")?;
+
+    Some((key_len, value))
+}
+
+/// End of a `print_r` value starting at `start`: the end of the line, the `[key] =>` of the
+/// next member, or a `)` that the value did not open. The `(` after a nested `Array` or
+/// `Name Object` header ends it as well, so the header is the value.
+fn print_r_value_end(input: &str, start: usize) -> usize {
+    let mut depth = 0_usize;
+
+    for (offset, byte) in input[start..].bytes().enumerate() {
+        let position = start + offset;
+        match byte {
+            b'\n' | b'\r' => return position,
+            b')' if depth == 0 => return position,
+            b')' => depth -= 1,
+            b'(' => {
+                let header = input[start..position].trim_end();
+                if header.eq_ignore_ascii_case("array") || header.ends_with(" Object") {
+                    return position;
+                }
+                depth += 1;
+            }
+            b'[' if depth == 0 && print_r_member(&input[position..]).is_some() => {
+                return position;
+            }
+            _ => {}
+        }
+    }
+
+    input.len()
+}
+
+/// Whether the `:` at `position` starts a Ruby symbol such as `:name` or `:"a b"` rather
+/// than separating a key from its value. The `:` itself is skipped, so the symbol reads as the
+/// name or string after it.
+fn is_symbol_start(bytes: &[u8], position: usize, options: &PrepareOptions) -> bool {
+    let follows_boundary = position
+        .checked_sub(1)
+        .map(|previous| bytes[previous])
+        .is_none_or(|previous| {
+            previous.is_ascii_whitespace()
+                || matches!(previous, b'{' | b'[' | b'(' | b'>')
+                || options.is_separator(previous)
+        });
+    let starts_name = bytes
+        .get(position + 1)
+        .is_some_and(|&next| next.is_ascii_alphabetic() || matches!(next, b'_' | b'"' | b'\''));
+
+    bytes[position] == b':' && follows_boundary && starts_name
+}
+
+fn string_kind(terminated: bool) -> TokenKind {
+    if terminated {
+        TokenKind::String
+    } else {
+        TokenKind::UnterminatedString
+    }
+}
+
+/// Whether the whitespace ending at `next` separates two elements or members. It only does
+/// inside brackets, between a value and the start of another. In a body holding `key: value`
+/// pairs it also needs a `key:` to follow, so values such as `pump station` or
+/// `2024-01-02 15:04:05` stay whole.
+fn whitespace_separates(
+    input: &str,
+    next: usize,
+    tokens: &[Token],
+    keyed_bodies: &[bool],
+    options: &PrepareOptions,
+) -> bool {
+    let syntax = options.dialect.syntax();
+    let after_value = matches!(
+        previous_kind(tokens),
+        Some(TokenKind::String | TokenKind::Text | TokenKind::RightBrace | TokenKind::RightBracket)
+    );
+    let before_value = input.as_bytes().get(next).is_some_and(|&byte| {
+        !matches!(byte, b'}' | b']')
+            && !syntax.is_pair_separator(byte)
+            && !options.is_separator(byte)
+            && whitespace_len(input, next, &syntax) == 0
+    });
+
+    let separates = match keyed_bodies.last() {
+        Some(true) => after_value && before_value && is_key_ahead(input, next, options, &syntax),
+        Some(false) => after_value && before_value,
+        None => false,
+    };
+
+    separates
+}
+
+/// Whether a `key:` starts at `start`, where the value after the `:` is not itself followed by
+/// a `:` as in the time `15:04:05`.
+fn is_key_ahead(input: &str, start: usize, options: &PrepareOptions, syntax: &Syntax) -> bool {
+    let bytes = input.as_bytes();
+    let key_end = text_end(input, start, options, syntax);
+    if !bytes
+        .get(key_end)
+        .is_some_and(|&byte| syntax.is_pair_separator(byte))
+    {
+        return false;
+    }
+
+    let value_start = key_end + 1;
+    let value_end = text_end(input, value_start, options, syntax);
+    let is_key_ahead = value_end == value_start
+        || !matches!(bytes.get(value_start), Some(byte) if byte.is_ascii_digit())
+        || !bytes
+            .get(value_end)
+            .is_some_and(|&byte| syntax.is_pair_separator(byte));
+
+    is_key_ahead
+}
+
+/// Whether the next token starts an object member's value or an array element.
+fn is_value_position(tokens: &[Token], open_brackets: &[TokenKind]) -> bool {
+    let is_value_position = match previous_kind(tokens) {
+        Some(TokenKind::Colon | TokenKind::LeftBracket) => true,
+        Some(TokenKind::Separator) => open_brackets.last() == Some(&TokenKind::LeftBracket),
+        _ => false,
+    };
+
+    is_value_position
+}
+
+/// End of an Hjson quoteless string starting at `start`, or `None` if the value there is
+/// something else. Quoteless strings run to the end of the line, commas and comments
+/// included, but a line holding only a number, `true`, `false` or `null`, optionally followed
+/// by a `,` or a comment, is that literal instead. A closing bracket that the line did not
+/// open ends the string early, so one-line objects like `{a: b}` still work.
+fn quoteless_end(
+    input: &str,
+    start: usize,
+    options: &PrepareOptions,
+    syntax: &Syntax,
+    inside_brackets: bool,
+) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let first = bytes[start];
+    if matches!(first, b'{' | b'}' | b'[' | b']')
+        || syntax.is_quote(first)
+        || options.is_separator(first)
+    {
+        return None;
+    }
+
+    let rest = &input[start..];
+    let head_len = rest
+        .char_indices()
+        .find(|&(offset, ch)| {
+            ch.is_whitespace()
+                || ch == ','
+                || (offset > 0 && comment_len(bytes, start + offset, options, syntax) > 0)
+        })
+        .map_or(rest.len(), |(offset, _)| offset);
+    let head = &rest[..head_len];
+    let after_head = rest[head_len..].trim_start_matches([' ', '\t']);
+    let is_literal =
+        matches!(head, "true" | "false" | "null") || head.parse::<f64>().is_ok_and(f64::is_finite);
+    let is_literal_line = after_head.is_empty()
+        || after_head.starts_with([',', '\n', '\r'])
+        || comment_len(bytes, input.len() - after_head.len(), options, syntax) > 0;
+    if is_literal && is_literal_line {
+        return None;
+    }
+
+    let mut depth = 0_usize;
+    let mut end = input.len();
+    for (offset, byte) in rest.bytes().enumerate() {
+        match byte {
+            b'\n' | b'\r' => {
+                end = start + offset;
+                break;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth == 0 && inside_brackets => {
+                end = start + offset;
+                break;
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    Some(start + input[start..end].trim_end().len())
+}
+
+/// Inputs that went through one or two rounds of escaping carry newlines as the
+/// literal characters `\n` or `\\n`, which are treated as whitespace between tokens.
+fn escaped_newline_len(bytes: &[u8], options: &PrepareOptions) -> usize {
+    if !options.trim_escaped_newlines {
+        return 0;
+    }
+
+    let escaped_newline_len = match bytes {
+        [b'\\', b'n', ..] => 2,
+        [b'\\', b'\\', b'n', ..] => 3,
+        _ => 0,
+    };
+
+    escaped_newline_len
+}
+
+fn whitespace_len(input: &str, position: usize, syntax: &Syntax) -> usize {
+    let whitespace_len = match input.get(position..).and_then(|rest| rest.chars().next()) {
+        Some(ch) if ch.is_ascii_whitespace() => 1,
+        Some(ch) if syntax.unicode_whitespace && (ch.is_whitespace() || ch == '\u{feff}') => {
+            ch.len_utf8()
+        }
+        _ => 0,
+    };
+
+    whitespace_len
+}
+
+/// Length of a comment starting at `position`, or 0 if none starts there. Comments of the
+/// styles picked with [`PrepareOptions::comments`] only start at a token boundary, so `//` in
+/// `http://host` is text. The dialect's own comments may follow a value directly, as in `1//c`
+/// or `1/*c*/`, except that `//` never starts right after a `:`. An unterminated block comment
+/// runs to the end of the input.
+fn comment_len(bytes: &[u8], position: usize, options: &PrepareOptions, syntax: &Syntax) -> usize {
+    let rest = &bytes[position..];
+    let previous = position.checked_sub(1).map(|previous| bytes[previous]);
+    let at_boundary = if options.comments.is_some() {
+        previous.is_none_or(|previous| {
+            previous.is_ascii_whitespace()
+                || matches!(previous, b'{' | b'}' | b'[' | b']')
+                || syntax.is_quote(previous)
+                || options.is_separator(previous)
+        })
+    } else {
+        previous != Some(b':') || !rest.starts_with(b"//")
+    };
+    if !at_boundary {
+        return 0;
+    }
+
+    let comment_end =
+        options
+            .comment_styles()
+            .iter()
+            .find_map(|comment_style| match comment_style {
+                CommentStyle::DoubleSlash if rest.starts_with(b"//") => Some(line_len(rest)),
+                CommentStyle::Hash if rest.starts_with(b"#") => Some(line_len(rest)),
+                CommentStyle::SlashStar if rest.starts_with(b"/*") => Some(
+                    rest[2..]
+                        .windows(2)
+                        .position(|window| window == b"*/")
+                        .map_or(rest.len(), |close| close + 4),
+                ),
+                _ => None,
+            });
+
+    comment_end.unwrap_or(0)
+}
+
+fn line_len(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|&byte| matches!(byte, b'\n' | b'\r'))
+        .unwrap_or(bytes.len())
+}
+
+/// Tokens for the forms of a dialect that the generic rules would split wrongly, and where they
+/// end. See [`php_tokens`] and [`java_token`].
+fn dialect_tokens(
+    input: &str,
+    position: usize,
+    after_colon: bool,
+    equals_ahead: &mut Option<(usize, bool)>,
+    options: &PrepareOptions,
+) -> Option<(Vec<Token>, usize)> {
+    if options.dialect.syntax().php_arrays {
+        php_tokens(input, position)
+    } else {
+        java_token(
+            input.as_bytes(),
+            position,
+            after_colon,
+            equals_ahead,
+            options,
+        )
+    }
+}
+
+/// Lexes the parts of Java `toString()` output that the generic rules would split wrongly:
+/// - a `:` inside a key, as in `urn:a=1`, which is split at the `=` instead,
+/// - a whole value in single quotes, as in `name='Ann'`, which is a string.
+///
+/// `equals_ahead` keeps the result of the last [`scan_equals`], which serves every `:` up to
+/// where that scan stopped and so keeps lexing linear.
+fn java_token(
+    bytes: &[u8],
+    position: usize,
+    after_colon: bool,
+    equals_ahead: &mut Option<(usize, bool)>,
+    options: &PrepareOptions,
+) -> Option<(Vec<Token>, usize)> {
+    let syntax = options.dialect.syntax();
+    let byte = bytes[position];
+
+    let (kind, end) = if syntax.equals_first && byte == b':' {
+        if equals_ahead.is_none_or(|(scan_end, _)| scan_end <= position) {
+            *equals_ahead = Some(scan_equals(bytes, position + 1, options));
+        }
+        let is_key_colon =
+            equals_ahead.is_some_and(|(scan_end, found)| found && position < scan_end);
+        (TokenKind::Text, is_key_colon.then_some(position + 1)?)
+    } else if after_colon && syntax.value_quotes.contains(&byte) {
+        (
+            TokenKind::String,
+            value_quote_end(bytes, position, options)?,
+        )
+    } else {
+        return None;
+    };
+
+    let token = Token {
+        kind,
+        span: Span::new(position, end),
+    };
+
+    Some((vec![token], end))
+}
+
+/// Scans the rest of a member from `start` for an `=`, which a member holding both pair
+/// separators is split at instead of a `:`. Returns where the scan stopped and whether it found
+/// one; it stops at the end of the member or at a bracket or quote.
+fn scan_equals(bytes: &[u8], start: usize, options: &PrepareOptions) -> (usize, bool) {
+    for (offset, &byte) in bytes[start..].iter().enumerate() {
+        match byte {
+            b'=' => return (start + offset, true),
+            b'{' | b'}' | b'[' | b']' | b'(' | b')' | b'"' | b'\n' | b'\r' => {
+                return (start + offset, false)
+            }
+            byte if options.is_separator(byte) => return (start + offset, false),
+            _ => {}
+        }
+    }
+
+    (bytes.len(), false)
+}
+
+/// End of a value quoted with a value quote, as in `name='Ann'`: the next such quote, if only
+/// a separator, a closer or the end of the line follows it.
+fn value_quote_end(bytes: &[u8], start: usize, options: &PrepareOptions) -> Option<usize> {
+    let quote = bytes[start];
+    let close = start + 1 + bytes[start + 1..].iter().position(|&byte| byte == quote)?;
+
+    let is_whole_value = bytes[close + 1..]
+        .iter()
+        .find(|&&byte| !matches!(byte, b' ' | b'\t'))
+        .is_none_or(|&byte| {
+            matches!(byte, b'}' | b']' | b')' | b'\n' | b'\r') || options.is_separator(byte)
+        });
+
+    is_whole_value.then_some(close + 1)
+}
+
+fn string_end(bytes: &[u8], start: usize) -> (usize, bool) {
+    let quote = bytes[start];
+    let mut position = start + 1;
+
+    while position < bytes.len() {
+        match bytes[position] {
+            b'\\' => position += 2,
+            byte if byte == quote => return (position + 1, true),
+            _ => position += 1,
+        }
+    }
+
+    (bytes.len(), false)
+}
+
+fn text_end(input: &str, start: usize, options: &PrepareOptions, syntax: &Syntax) -> usize {
+    let bytes = input.as_bytes();
+    let mut position = start;
+
+    while position < bytes.len() {
+        let byte = bytes[position];
+        let is_boundary = whitespace_len(input, position, syntax) > 0
+            || comment_len(bytes, position, options, syntax) > 0
+            || matches!(byte, b'{' | b'}' | b'[' | b']')
+            || syntax.is_pair_separator(byte)
+            || syntax.is_quote(byte)
+            || syntax.is_paren(byte)
+            || (syntax.fat_arrows && bytes[position..].starts_with(b"=>"))
+            || options.is_separator(byte)
+            || escaped_newline_len(&bytes[position..], options) > 0;
+
+        if is_boundary && position > start {
+            break;
+        }
+        position += 1;
+    }
+
+    position
+}
+
+/// Decodes a quoted string token, including any prefix letters such as the `b` in `b'..'`.
+/// Strings with an `r` prefix are raw and keep their backslashes.
+pub(crate) fn decode_string(raw: &str, syntax: &Syntax) -> String {
+    let prefix_len = syntax.string_prefix_len(raw.as_bytes());
+    let (prefix, quoted) = raw.split_at(prefix_len);
+
+    let quote = quoted.chars().next().unwrap_or('"');
+    let content = quoted.strip_prefix(quote).unwrap_or(quoted);
+    let content = content.strip_suffix(quote).unwrap_or(content);
+
+    if prefix.contains(['r', 'R']) {
+        return content.to_string();
+    }
+
+    if syntax.escapes == Escapes::Ruby && quote == '\'' {
+        return decode_single_quoted(content);
+    }
+
+    let mut decoded = String::with_capacity(content.len());
+    let mut chars = content.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            decoded.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('"') => decoded.push('"'),
+            Some('/') => decoded.push('/'),
+            Some('b') => decoded.push('\u{8}'),
+            Some('f') => decoded.push('\u{c}'),
+            Some('n') => decoded.push('\n'),
+            Some('r') => decoded.push('\r'),
+            Some('t') => decoded.push('\t'),
+            Some('u') => {
+                let (unicode_char, consumed) = match chars.as_str() {
+                    rest if matches!(syntax.escapes, Escapes::JavaScript | Escapes::Ruby)
+                        && rest.starts_with('{') =>
+                    {
+                        decode_braced_unicode_escape(rest)
+                    }
+                    rest => decode_unicode_escape(rest),
+                };
+                match unicode_char {
+                    Some(unicode_char) => {
+                        decoded.push(unicode_char);
+                        chars = chars.as_str()[consumed..].chars();
+                    }
+                    None => decoded.push_str("\\u"),
+                }
+            }
+            Some('\\') | None => decoded.push('\\'),
+            Some(other) if syntax.escapes != Escapes::Json => {
+                let rest = chars.as_str();
+                if let Some(consumed) =
+                    decode_extended_escape(syntax.escapes, other, rest, &mut decoded)
+                {
+                    chars = chars.as_str()[consumed..].chars();
+                } else {
+                    decoded.push('\\');
+                    decoded.push(other);
+                }
+            }
+            Some(other) => {
+                decoded.push('\\');
+                decoded.push(other);
+            }
+        }
+    }
+
+    decoded
+}
+
+/// Single-quoted Ruby and Perl strings only escape the quote and the backslash; any other
+/// backslash is kept.
+fn decode_single_quoted(content: &str) -> String {
+    let mut decoded = String::with_capacity(content.len());
+    let mut chars = content.chars();
+
+    while let Some(ch) = chars.next() {
+        match (ch, chars.clone().next()) {
+            ('\\', Some(escaped @ ('\\' | '\''))) => {
+                decoded.push(escaped);
+                chars.next();
+            }
+            _ => decoded.push(ch),
+        }
+    }
+
+    decoded
+}
+
+/// Escapes beyond JSON's, shared by the dialects whose strings use them. Pushes the decoded
+/// character, if any (a line continuation decodes to nothing), and returns how many bytes of
+/// `rest` the escape used, or `None` for an unknown escape. JavaScript and Ruby have no
+/// unknown escapes: a backslash before any other character is dropped.
+fn decode_extended_escape(
+    escapes: Escapes,
+    escape: char,
+    rest: &str,
+    decoded: &mut String,
+) -> Option<usize> {
+    let hex_char = |len: usize| {
+        rest.get(..len)
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+    };
+
+    let consumed = match escape {
+        '\'' => {
+            decoded.push('\'');
+            0
+        }
+        'a' if escapes == Escapes::Python => {
+            decoded.push('\u{7}');
+            0
+        }
+        'v' => {
+            decoded.push('\u{b}');
+            0
+        }
+        '\n' => 0,
+        '\r' => usize::from(rest.starts_with('\n')),
+        '\u{2028}' | '\u{2029}' if escapes == Escapes::JavaScript => 0,
+        'e' if escapes == Escapes::Ruby => {
+            decoded.push('\u{1b}');
+            0
+        }
+        's' if escapes == Escapes::Ruby => {
+            decoded.push(' ');
+            0
+        }
+        'x' => {
+            decoded.push(hex_char(2)?);
+            2
+        }
+        'U' if escapes == Escapes::Python => {
+            decoded.push(hex_char(8)?);
+            8
+        }
+        '0'..='7' => {
+            let octal_len = rest
+                .bytes()
+                .take(2)
+                .take_while(|byte| matches!(byte, b'0'..=b'7'))
+                .count();
+            let octal = format!("{escape}{}", &rest[..octal_len]);
+            decoded.extend(u32::from_str_radix(&octal, 8).ok().and_then(char::from_u32));
+            octal_len
+        }
+        other if matches!(escapes, Escapes::JavaScript | Escapes::Ruby) => {
+            decoded.push(other);
+            0
+        }
+        _ => return None,
+    };
+
+    Some(consumed)
+}
+
+/// Decodes an Hjson `'''` string. `indent` is the column of the opening quotes: that much
+/// leading whitespace is stripped from every line after the first, a first line holding only
+/// whitespace is dropped, and so is the line break before the closing quotes.
+pub(crate) fn decode_multiline_string(raw: &str, indent: usize) -> String {
+    let content = raw.strip_prefix("'''").unwrap_or(raw);
+    let content = content.strip_suffix("'''").unwrap_or(content);
+    let (content, first_indented_line) = match content.split_once('\n') {
+        Some((first_line, rest)) if first_line.trim().is_empty() => (rest, 0),
+        _ => (content, 1),
+    };
+
+    let decoded = content
+        .split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let indent_len = line
+                .chars()
+                .take(if index < first_indented_line {
+                    0
+                } else {
+                    indent
+                })
+                .take_while(|ch| ch.is_whitespace())
+                .map(char::len_utf8)
+                .sum::<usize>();
+
+            &line[indent_len..]
+        })
+        .join("\n");
+
+    match decoded.rsplit_once('\n') {
+        Some((before_last_line, last_line)) if last_line.trim().is_empty() => {
+            before_last_line.to_string()
+        }
+        _ => decoded,
+    }
+}
+
+/// Decodes the `\u` escapes JavaScript allows in unquoted identifiers, as in `\u0061b`.
+pub(crate) fn decode_identifier(raw: &str) -> String {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(escape_start) = rest.find("\\u") {
+        decoded.push_str(&rest[..escape_start]);
+        let escape = &rest[escape_start + 2..];
+        let (unicode_char, consumed) = if escape.starts_with('{') {
+            decode_braced_unicode_escape(escape)
+        } else {
+            decode_unicode_escape(escape)
+        };
+
+        match unicode_char {
+            Some(unicode_char) => decoded.push(unicode_char),
+            None => decoded.push_str("\\u"),
+        }
+        rest = &escape[consumed..];
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+fn decode_braced_unicode_escape(rest: &str) -> (Option<char>, usize) {
+    let Some(close) = rest.find('}') else {
+        return (None, 0);
+    };
+
+    let unicode_char = u32::from_str_radix(&rest[1..close], 16)
+        .ok()
+        .and_then(char::from_u32);
+
+    match unicode_char {
+        Some(unicode_char) => (Some(unicode_char), close + 1),
+        None => (None, 0),
+    }
+}
+
+fn decode_unicode_escape(rest: &str) -> (Option<char>, usize) {
+    let Some(high) = rest
+        .get(..4)
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+    else {
+        return (None, 0);
+    };
+
+    if !(0xD800..0xDC00).contains(&high) {
+        return (char::from_u32(high), 4);
+    }
+
+    let low = rest
+        .get(4..10)
+        .and_then(|escape| escape.strip_prefix("\\u"))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .filter(|low| (0xDC00..0xE000).contains(low));
+
+    match low {
+        Some(low) => {
+            let code_point = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+            (char::from_u32(code_point), 10)
+        }
+        None => (None, 0),
+    }
+}
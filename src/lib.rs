@@ -1,7 +1,35 @@
 pub mod helpers;
+mod lexer;
+mod parser;
 pub mod public;
+mod syntax;
 
-pub use public::prepare_json_array::prepare_json_array;
-pub use public::prepare_json_string::prepare_json_string;
-pub use public::stringified_json_array::prepare_stringified_json_array;
-pub use public::stringified_json_string::prepare_stringified_json_string;
+pub use public::deserialize_error::{DeserializeError, Position};
+pub use public::lenient_deserializer::{from_str, LenientDeserializer};
+pub use public::lenient_value::{LenientKey, LenientKind, LenientMember, LenientValue, Span};
+pub use public::parse_lenient_json_string::parse_lenient_json_string;
+pub use public::prepare_error::{PrepareError, PrepareErrorKind};
+pub use public::prepare_fragment::{prepare_fragment, try_prepare_fragment, FragmentKind};
+pub use public::prepare_json_array::{
+    prepare_json_array, prepare_json_array_cow, try_prepare_json_array,
+};
+pub use public::prepare_json_string::{
+    prepare_json_string, prepare_json_string_cow, try_prepare_json_string,
+};
+pub use public::prepare_options::{
+    CommentStyle, Dialect, InvalidLiteralPolicy, KeylessPolicy, PrepareOptions, TopLevel, ValueMode,
+};
+pub use public::prepare_to_value::{prepare_array_to_value, prepare_to_value};
+pub use public::prepare_with_report::prepare_with_report;
+pub use public::preparer::Preparer;
+pub use public::repair::{Repair, RepairKind};
+pub use public::repairing_reader::RepairingReader;
+pub use public::stringified_json_array::{
+    prepare_stringified_json_array, try_prepare_stringified_json_array,
+};
+pub use public::stringified_json_string::{
+    prepare_stringified_json_string, try_prepare_stringified_json_string,
+};
+pub use public::stringified_to_value::{
+    prepare_stringified_array_to_value, prepare_stringified_to_value,
+};
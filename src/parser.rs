@@ -0,0 +1,1472 @@
+use std::{borrow::Cow, ops::Range};
+
+use itertools::Itertools;
+
+use crate::{
+    helpers::JsonContext,
+    lexer::{
+        decode_identifier, decode_multiline_string, decode_string, tokenize, Token, TokenKind,
+    },
+    public::{
+        lenient_value::{LenientKey, LenientKind, LenientMember, LenientValue, Span},
+        prepare_error::{PrepareError, PrepareErrorKind},
+        prepare_options::{InvalidLiteralPolicy, KeylessPolicy, PrepareOptions, ValueMode},
+        repair::{Repair, RepairKind},
+    },
+    syntax::{Escapes, Syntax},
+};
+
+pub(crate) struct ParseOutcome {
+    pub(crate) lenient_value: LenientValue,
+    pub(crate) errors: Vec<PrepareError>,
+    pub(crate) repairs: Vec<Repair>,
+}
+
+pub(crate) fn parse(
+    input: &str,
+    json_context: JsonContext,
+    options: &PrepareOptions,
+) -> LenientValue {
+    let parse_outcome = parse_outcome(input, json_context, options);
+
+    parse_outcome.lenient_value
+}
+
+pub(crate) fn parse_outcome(
+    input: &str,
+    json_context: JsonContext,
+    options: &PrepareOptions,
+) -> ParseOutcome {
+    let mut parser = Parser::new(input, options);
+
+    let lenient_value = match json_context {
+        JsonContext::Array => {
+            let elements = parser.parse_array_elements();
+            LenientValue::new(LenientKind::Array(elements), parser.whole_span())
+        }
+        JsonContext::Object => {
+            let object_kind = parser.parse_object_body();
+            LenientValue::new(object_kind, parser.whole_span())
+        }
+        JsonContext::Value => parser.parse_document_value(),
+    };
+
+    if parser.tokens.iter().all(Token::is_separator) {
+        parser.record_error(PrepareErrorKind::EmptyInput, Span::new(0, 0));
+    }
+
+    parser.record_escaped_newline_repairs();
+    parser.record_comment_repairs();
+    parser.repairs.sort_by_key(|repair| repair.span.start);
+
+    ParseOutcome {
+        lenient_value,
+        errors: parser.errors,
+        repairs: parser.repairs,
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    options: &'a PrepareOptions,
+    syntax: Syntax,
+    tokens: Vec<Token>,
+    position: usize,
+    open_closers: Vec<TokenKind>,
+    errors: Vec<PrepareError>,
+    repairs: Vec<Repair>,
+    escaped_newlines: Vec<Span>,
+    comments: Vec<Span>,
+    run_spans: Vec<Span>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str, options: &'a PrepareOptions) -> Self {
+        let (skipped, tokens): (Vec<_>, Vec<_>) =
+            tokenize(input, options).into_iter().partition(|token| {
+                matches!(token.kind, TokenKind::EscapedNewline | TokenKind::Comment)
+            });
+        let (escaped_newlines, comments): (Vec<Token>, Vec<Token>) = skipped
+            .into_iter()
+            .partition(|token| token.kind == TokenKind::EscapedNewline);
+
+        let mut parser = Self {
+            input,
+            options,
+            syntax: options.dialect.syntax(),
+            tokens,
+            position: 0,
+            open_closers: Vec::new(),
+            errors: Vec::new(),
+            repairs: Vec::new(),
+            escaped_newlines: escaped_newlines.iter().map(|token| token.span).collect(),
+            comments: comments.iter().map(|token| token.span).collect(),
+            run_spans: Vec::new(),
+        };
+
+        let unterminated_strings = parser
+            .tokens
+            .iter()
+            .filter(|token| token.kind == TokenKind::UnterminatedString)
+            .map(|token| token.span)
+            .collect::<Vec<_>>();
+        for span in unterminated_strings {
+            parser.record_error(PrepareErrorKind::UnterminatedString, span);
+            parser.record_repair(
+                RepairKind::ClosedString,
+                Span::new(span.end, span.end),
+                "added a closing `\"` to an unterminated string",
+            );
+        }
+
+        parser
+    }
+
+    fn record_error(&mut self, kind: PrepareErrorKind, span: Span) {
+        let error = PrepareError::new(kind, self.input, span.start);
+        self.errors.push(error);
+    }
+
+    fn record_repair(&mut self, kind: RepairKind, span: Span, message: impl Into<String>) {
+        self.repairs.push(Repair::new(kind, span, message));
+    }
+
+    /// Escaped newlines inside keys and scalar values are kept as written; only those
+    /// between tokens are dropped.
+    fn record_escaped_newline_repairs(&mut self) {
+        let dropped_newlines =
+            self.escaped_newlines
+                .iter()
+                .filter(|newline| {
+                    !self.run_spans.iter().any(|run_span| {
+                        run_span.start < newline.start && newline.end < run_span.end
+                    })
+                })
+                .copied()
+                .collect::<Vec<_>>();
+
+        for span in dropped_newlines {
+            self.record_repair(
+                RepairKind::DroppedEscapedNewline,
+                span,
+                format!("dropped escaped newline `{}`", self.slice(span)),
+            );
+        }
+    }
+
+    fn record_comment_repairs(&mut self) {
+        if !self.options.report_comments {
+            return;
+        }
+
+        for span in self.comments.clone() {
+            self.record_repair(
+                RepairKind::DroppedComment,
+                span,
+                format!("dropped comment `{}`", self.slice(span).trim_end()),
+            );
+        }
+    }
+
+    fn slice(&self, span: Span) -> &'a str {
+        &self.input[span.start..span.end]
+    }
+
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn whole_span(&self) -> Span {
+        match (self.tokens.first(), self.tokens.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => Span::new(0, 0),
+        }
+    }
+
+    fn empty_span_here(&self) -> Span {
+        let offset = match self.peek() {
+            Some(token) => token.span.start,
+            None => self.tokens.last().map_or(0, |token| token.span.end),
+        };
+
+        Span::new(offset, offset)
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(&Token) -> bool) -> Vec<Token> {
+        let mut taken = Vec::new();
+
+        while let Some(token) = self.peek().filter(|token| predicate(token)) {
+            taken.push(token);
+            self.position += 1;
+        }
+
+        taken
+    }
+
+    /// Line breaks that separate members in Hjson, and whitespace that separates them in Go,
+    /// are dropped or kept without a repair.
+    fn is_whitespace_separator(&self, separator: &Token) -> bool {
+        self.slice(separator.span).trim().is_empty()
+    }
+
+    fn drop_separators(&mut self, separators: &[Token]) {
+        let separators = separators
+            .iter()
+            .filter(|separator| !self.is_whitespace_separator(separator))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for separator in separators {
+            self.record_repair(
+                RepairKind::DroppedSeparator,
+                separator.span,
+                format!("dropped `{}`", self.slice(separator.span)),
+            );
+        }
+    }
+
+    /// Records what happens to the separators found between two elements or members:
+    /// the first one is kept as a `,` and any others are dropped. Separators before the
+    /// first or after the last element are all dropped.
+    fn keep_first_separator(&mut self, separators: &[Token], is_first_element: bool) {
+        if is_first_element || self.at_body_end() {
+            self.drop_separators(separators);
+            return;
+        }
+
+        let Some((kept, dropped)) = separators.split_first() else {
+            return;
+        };
+
+        if self.slice(kept.span) != "," && !self.is_whitespace_separator(kept) {
+            self.record_repair(
+                RepairKind::ReplacedSeparator,
+                kept.span,
+                format!("replaced `{}` with `,`", self.slice(kept.span)),
+            );
+        }
+        self.drop_separators(dropped);
+    }
+
+    fn parse_document_value(&mut self) -> LenientValue {
+        self.drop_assignment();
+
+        let leading =
+            self.take_while(|token| token.is_separator() || token.kind == TokenKind::Colon);
+        self.drop_separators(&leading);
+
+        if self.peek().is_none() {
+            return LenientValue::new(LenientKind::Empty, Span::new(0, 0));
+        }
+
+        if self.at_container() {
+            let container = self.parse_container();
+            let trailing =
+                self.take_while(|token| token.is_separator() || token.kind == TokenKind::Colon);
+            self.drop_separators(&trailing);
+
+            if let Some(unexpected) = self.peek() {
+                self.record_error(PrepareErrorKind::UnexpectedContent, unexpected.span);
+
+                let dropped_span = unexpected.span.to(self.tokens[self.tokens.len() - 1].span);
+                self.record_repair(
+                    RepairKind::DroppedContent,
+                    dropped_span,
+                    format!("dropped `{}` after the value", self.slice(dropped_span)),
+                );
+                self.position = self.tokens.len();
+            }
+
+            return container;
+        }
+
+        if self.looks_like_object_body() {
+            let object_kind = self.parse_object_body();
+            return LenientValue::new(object_kind, self.whole_span());
+        }
+
+        let last_index = self.tokens[self.position..]
+            .iter()
+            .rposition(|token| !token.is_separator() && token.kind != TokenKind::Colon)
+            .map_or(self.position, |offset| self.position + offset);
+
+        let trailing = self.tokens[last_index + 1..].to_vec();
+        self.drop_separators(&trailing);
+
+        let run = self.position..last_index + 1;
+        self.position = self.tokens.len();
+        self.record_stray_closers(&run);
+
+        self.scalar_from_run(run)
+    }
+
+    /// Records an error for each closer in a run read as a scalar, such as the `}` in `}{`, that
+    /// closes nothing opened before it in the run. The closer is kept as text.
+    fn record_stray_closers(&mut self, run: &Range<usize>) {
+        let mut depth = 0_usize;
+
+        for index in run.clone() {
+            let token = self.tokens[index];
+            if token.is_opener() {
+                depth += 1;
+            } else if token.is_closer() && depth > 0 {
+                depth -= 1;
+            } else if token.is_closer() {
+                self.record_error(PrepareErrorKind::UnbalancedBracket, token.span);
+            }
+        }
+    }
+
+    /// Drops a leading `$VAR1 =`, as printed by Perl's `Data::Dumper`. The `;` that ends the
+    /// statement is a trailing separator and is dropped with the others.
+    fn drop_assignment(&mut self) {
+        let Some(first) = self.peek().filter(|_| self.syntax.assignments) else {
+            return;
+        };
+
+        let Some(variable) = self.input[first.span.start..].strip_prefix('$') else {
+            return;
+        };
+        let name_len = variable
+            .find(|ch: char| !ch.is_alphanumeric() && ch != '_')
+            .unwrap_or(variable.len());
+        let Some(value) = variable[name_len..].trim_start().strip_prefix('=') else {
+            return;
+        };
+        if name_len == 0 || value.starts_with(['=', '>']) {
+            return;
+        }
+
+        let assignment_end = self.input.len() - value.len();
+        let assignment = self.take_while(|token| token.span.end <= assignment_end);
+        if let (Some(first), Some(last)) = (assignment.first(), assignment.last()) {
+            let span = first.span.to(last.span);
+            self.record_repair(
+                RepairKind::DroppedContent,
+                span,
+                format!("dropped the assignment `{}`", self.slice(span)),
+            );
+        }
+    }
+
+    /// Tells a brace-less `a: 1, b: 2` list apart from scalars that merely contain a colon,
+    /// such as `http://host` or `12:30`: the first token must be a quoted or identifier-like
+    /// key followed by a colon, and that colon (or, with the `namespaced_keys` option, the one
+    /// ending a namespaced key) must be followed by whitespace, or the input must contain a
+    /// separator.
+    fn looks_like_object_body(&self) -> bool {
+        let (Some(key), Some(colon)) = (
+            self.tokens.get(self.position),
+            self.tokens.get(self.position + 1),
+        ) else {
+            return false;
+        };
+
+        let is_key = match key.kind {
+            TokenKind::String => true,
+            TokenKind::Text => {
+                let raw = self.slice(key.span);
+                raw.starts_with(|ch: char| ch.is_alphabetic() || ch == '_' || ch == '$')
+                    && raw
+                        .chars()
+                        .all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '$' | '-' | '.'))
+            }
+            _ => false,
+        };
+        if !is_key || colon.kind != TokenKind::Colon {
+            return false;
+        }
+
+        let is_spaced = self
+            .tokens
+            .get(self.position + 2)
+            .is_none_or(|value| value.span.start > colon.span.end);
+
+        key.kind == TokenKind::String
+            || is_spaced
+            || self.namespaced_key_end(self.position + 1) != self.position + 1
+            || self.tokens[self.position..].iter().any(Token::is_separator)
+    }
+
+    /// Whether the next token starts a container: an opener, or a call like `set()`
+    /// in dialects that have them.
+    fn at_container(&self) -> bool {
+        self.peek().is_some_and(|token| token.is_opener())
+            || self.at_call()
+            || self.at_php_array()
+            || self.at_named_object()
+            || self.at_go_map()
+    }
+
+    fn at_call(&self) -> bool {
+        let (Some(name), Some(paren)) = (
+            self.tokens.get(self.position),
+            self.tokens.get(self.position + 1),
+        ) else {
+            return false;
+        };
+
+        self.syntax.calls
+            && name.kind == TokenKind::Text
+            && paren.kind == TokenKind::LeftParen
+            && paren.span.start == name.span.end
+    }
+
+    /// Whether a PHP array starts here: `[...]`, or `array (...)`, `Array (...)` or
+    /// `Name Object (...)` with any whitespace before the paren.
+    fn at_php_array(&self) -> bool {
+        if !self.syntax.php_arrays {
+            return false;
+        }
+
+        let at_php_array = match (
+            self.tokens.get(self.position),
+            self.tokens.get(self.position + 1),
+        ) {
+            (Some(bracket), _) if bracket.kind == TokenKind::LeftBracket => true,
+            (Some(name), Some(paren)) => {
+                let name_str = self.slice(name.span);
+                name.kind == TokenKind::Text
+                    && paren.kind == TokenKind::LeftParen
+                    && (name_str.eq_ignore_ascii_case("array") || name_str.ends_with(" Object"))
+            }
+            _ => false,
+        };
+
+        at_php_array
+    }
+
+    /// Whether a `Name(...)`, `Name[...]` or `Name {...}` object starts here. Parens and
+    /// brackets must follow the name directly, so `Pump (1)` stays text.
+    fn at_named_object(&self) -> bool {
+        let (Some(name), Some(opener)) = (
+            self.tokens.get(self.position),
+            self.tokens.get(self.position + 1),
+        ) else {
+            return false;
+        };
+
+        let name_str = self.slice(name.span);
+        let is_type_name = name.kind == TokenKind::Text
+            && name_str.starts_with(|ch: char| ch.is_alphabetic() || ch == '_')
+            && name_str
+                .strip_suffix('!')
+                .filter(|_| self.syntax.rust_values)
+                .unwrap_or(name_str)
+                .chars()
+                .all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '.' | '$'));
+        let is_attached = opener.kind == TokenKind::LeftBrace
+            || (opener.is_opener() && opener.span.start == name.span.end);
+
+        self.syntax.named_objects && is_type_name && is_attached
+    }
+
+    /// Whether a Go `map[...]` starts here.
+    fn at_go_map(&self) -> bool {
+        let (Some(name), Some(bracket)) = (
+            self.tokens.get(self.position),
+            self.tokens.get(self.position + 1),
+        ) else {
+            return false;
+        };
+
+        self.syntax.go_values
+            && name.kind == TokenKind::Text
+            && self.slice(name.span) == "map"
+            && bracket.kind == TokenKind::LeftBracket
+            && bracket.span.start == name.span.end
+    }
+
+    /// Whether the body starting at the current position is empty or holds `key = value`
+    /// pairs. Other named bodies, like `Optional[5]`, are read as call arguments.
+    fn at_pairs_body(&self) -> bool {
+        let mut depth = 0_usize;
+        let mut has_elements = false;
+
+        for token in &self.tokens[self.position..] {
+            if token.is_opener() {
+                depth += 1;
+            } else if token.is_closer() {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            } else if depth == 0 && token.kind == TokenKind::Colon {
+                return true;
+            }
+
+            has_elements |= !token.is_separator();
+        }
+
+        !has_elements
+    }
+
+    /// Whether the call arguments starting at the current position are `key=value` pairs.
+    fn at_keyword_arguments(&self) -> bool {
+        self.peek().is_some_and(|token| !token.is_closer()) && self.at_pairs_body()
+    }
+
+    /// Whether the bracket body starting at the current position is a Swift dictionary: it holds
+    /// `key: value` pairs, or is the empty `[:]`, whose colon is skipped.
+    fn at_dictionary_body(&mut self, opener: Token) -> bool {
+        if !self.syntax.dictionaries || opener.kind != TokenKind::LeftBracket {
+            return false;
+        }
+
+        let is_empty = matches!(
+            (
+                self.tokens.get(self.position),
+                self.tokens.get(self.position + 1)
+            ),
+            (Some(colon), Some(closer))
+                if colon.kind == TokenKind::Colon && closer.kind == TokenKind::RightBracket
+        );
+        if is_empty {
+            self.position += 1;
+            return true;
+        }
+
+        let mut depth = 0_usize;
+
+        for token in &self.tokens[self.position..] {
+            if token.is_opener() {
+                depth += 1;
+            } else if token.is_closer() {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            } else if depth == 0 && token.kind == TokenKind::Colon {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Whether the member starting here has a key, that is, a colon before the next separator.
+    fn at_keyed_member(&self) -> bool {
+        let mut depth = 0_usize;
+
+        for token in &self.tokens[self.position..] {
+            if token.is_opener() {
+                depth += 1;
+            } else if token.is_closer() {
+                if depth == 0 {
+                    return false;
+                }
+                depth -= 1;
+            } else if depth == 0 && token.is_separator() {
+                return false;
+            } else if depth == 0 && token.kind == TokenKind::Colon {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Whether the brace body starting at the current position holds set elements rather than
+    /// `key: value` pairs, which is the case when it is not empty and has no colon of its own.
+    fn at_set_body(&self) -> bool {
+        if !self.syntax.sets {
+            return false;
+        }
+
+        let mut depth = 0_usize;
+        let mut has_elements = false;
+
+        for token in &self.tokens[self.position..] {
+            if token.is_opener() {
+                depth += 1;
+            } else if token.is_closer() {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            } else if depth == 0 && token.kind == TokenKind::Colon {
+                return false;
+            }
+
+            has_elements |= !token.is_separator();
+        }
+
+        has_elements
+    }
+
+    fn parse_container(&mut self) -> LenientValue {
+        let is_php_array = self.at_php_array();
+        let is_named_object = self.at_named_object();
+        let is_go_map = self.at_go_map();
+        let has_name = self.at_call()
+            || is_named_object
+            || is_go_map
+            || (is_php_array
+                && self
+                    .peek()
+                    .is_some_and(|token| token.kind == TokenKind::Text));
+        let call_name = has_name.then(|| self.tokens[self.position]);
+        let is_python_call = call_name.is_some() && self.syntax.python_values;
+        if call_name.is_some() {
+            self.position += 1;
+        }
+
+        let Some(opener) = self.peek() else {
+            return LenientValue::new(LenientKind::Empty, self.empty_span_here());
+        };
+        self.position += 1;
+
+        let closer = match opener.kind {
+            TokenKind::LeftBrace => TokenKind::RightBrace,
+            TokenKind::LeftParen => TokenKind::RightParen,
+            _ => TokenKind::RightBracket,
+        };
+
+        self.open_closers.push(closer);
+        let kind = if is_php_array {
+            self.parse_php_array_body()
+        } else if is_go_map
+            || self.at_dictionary_body(opener)
+            || (is_named_object && self.at_pairs_body())
+            || (is_python_call && self.at_keyword_arguments())
+            || (opener.kind == TokenKind::LeftBrace && !self.at_set_body())
+        {
+            self.parse_object_body()
+        } else {
+            LenientKind::Array(self.parse_array_elements())
+        };
+        self.open_closers.pop();
+
+        let end_span = match self.peek() {
+            Some(token) if token.kind == closer => {
+                self.position += 1;
+                token.span
+            }
+            unexpected => {
+                let (error_span, inserted_at) = match unexpected {
+                    Some(token) => (token.span, token.span.start),
+                    None => (opener.span, self.tokens[self.position - 1].span.end),
+                };
+                let closer_str = match closer {
+                    TokenKind::RightBrace => "}",
+                    TokenKind::RightParen => ")",
+                    _ => "]",
+                };
+
+                self.record_error(PrepareErrorKind::UnbalancedBracket, error_span);
+                self.record_repair(
+                    RepairKind::ClosedBracket,
+                    Span::new(inserted_at, inserted_at),
+                    format!("inserted missing `{closer_str}`"),
+                );
+                self.tokens[self.position - 1].span
+            }
+        };
+
+        let span = call_name.unwrap_or(opener).span.to(end_span);
+        if let Some(name) = call_name.filter(|_| is_python_call) {
+            return self.python_call(name, kind, span);
+        }
+
+        let is_macro = call_name.is_some_and(|name| self.slice(name.span).ends_with('!'));
+        let is_struct = matches!(kind, LenientKind::Object(_));
+        let container = match kind {
+            LenientKind::Array(mut arguments)
+                if call_name.is_some() && !is_php_array && !is_macro && arguments.len() == 1 =>
+            {
+                arguments.remove(0)
+            }
+            LenientKind::Array(arguments)
+                if self.syntax.rust_values
+                    && opener.kind == TokenKind::LeftParen
+                    && arguments.is_empty() =>
+            {
+                LenientValue::new(LenientKind::Null, span)
+            }
+            LenientKind::Object(mut members) if is_named_object => {
+                if let Some(name) = call_name {
+                    self.add_type_member(&mut members, name);
+                }
+                LenientValue::new(LenientKind::Object(members), span)
+            }
+            kind => LenientValue::new(kind, span),
+        };
+
+        match call_name {
+            Some(name) if self.syntax.rust_values && !is_macro && !is_struct => {
+                self.tag_variant(name, container, span)
+            }
+            _ => container,
+        }
+    }
+
+    /// Reads a Python constructor call. Mappings such as `dict(a=1)`, `OrderedDict([('a', 1)])`
+    /// or `defaultdict(<class 'list'>, {..})` become objects, and so do calls with `key=value`
+    /// arguments, such as a dataclass `Point(x=1, y=2)`, whose name is kept under the `type_key`
+    /// option. A call with one positional argument, like `Decimal('1.5')`, is that argument,
+    /// `set()` is an empty array, and other calls, like `datetime.date(2024, 1, 2)`, stay strings.
+    fn python_call(&mut self, name: Token, kind: LenientKind, span: Span) -> LenientValue {
+        let name_str = self.slice(name.span);
+        let type_name = name_str.rsplit('.').next().unwrap_or(name_str);
+        let is_mapping = matches!(
+            type_name,
+            "dict" | "OrderedDict" | "defaultdict" | "Counter"
+        );
+
+        let python_call = match kind {
+            LenientKind::Object(mut members) => {
+                if !is_mapping {
+                    self.add_type_member(&mut members, name);
+                }
+                LenientKind::Object(members)
+            }
+            LenientKind::Array(arguments) if is_mapping => {
+                let members = match arguments.into_iter().last() {
+                    Some(LenientValue {
+                        kind: LenientKind::Object(members),
+                        ..
+                    }) => members,
+                    Some(LenientValue {
+                        kind: LenientKind::Array(pairs),
+                        ..
+                    }) => pairs.into_iter().map(pair_member).collect(),
+                    _ => Vec::new(),
+                };
+                LenientKind::Object(members)
+            }
+            LenientKind::Array(mut arguments) if arguments.len() == 1 => {
+                return arguments.remove(0);
+            }
+            LenientKind::Array(arguments)
+                if arguments.is_empty() && matches!(type_name, "set" | "frozenset") =>
+            {
+                LenientKind::Array(arguments)
+            }
+            _ => LenientKind::Bare(self.slice(span).to_string()),
+        };
+
+        LenientValue::new(python_call, span)
+    }
+
+    /// Wraps the value of a Rust `Ok(..)` or `Err(..)` in an object keyed by the variant name.
+    /// Other tuple variants and tuple structs, such as `Parse(..)` or `Meters(..)`, are only
+    /// tagged with the `tag_tuple_variants` option, and `Some(..)` is always unwrapped.
+    fn tag_variant(&mut self, name: Token, value: LenientValue, span: Span) -> LenientValue {
+        let variant = self.slice(name.span);
+        let is_tagged = match variant {
+            "Ok" | "Err" => true,
+            "Some" => false,
+            _ => self.options.tag_tuple_variants,
+        };
+        if !is_tagged {
+            return value;
+        }
+
+        self.record_repair(
+            RepairKind::QuotedKey,
+            name.span,
+            format!("quoted key `{variant}`"),
+        );
+        let member = LenientMember {
+            key: Some(LenientKey {
+                name: variant.to_string(),
+                quoted: false,
+                span: name.span,
+            }),
+            value,
+        };
+
+        LenientValue::new(LenientKind::Object(vec![member]), span)
+    }
+
+    /// Keeps the name of a `Name(...)` object under the `type_key` option, if one is set.
+    fn add_type_member(&mut self, members: &mut Vec<LenientMember>, name: Token) {
+        let Some(type_key) = &self.options.type_key else {
+            return;
+        };
+
+        let type_name = self.slice(name.span);
+        let span = Span::new(name.span.start, name.span.start);
+        self.record_repair(
+            RepairKind::AddedKey,
+            span,
+            format!("added the key `{type_key}` holding the type name `{type_name}`"),
+        );
+
+        members.insert(
+            0,
+            LenientMember {
+                key: Some(LenientKey {
+                    name: type_key.clone(),
+                    quoted: true,
+                    span,
+                }),
+                value: LenientValue::new(LenientKind::String(type_name.to_string()), name.span),
+            },
+        );
+    }
+
+    /// Returns `true` when the body being parsed has reached its end: the input ran out
+    /// or a closer for this or an enclosing container was found. Closers that match
+    /// nothing that is open are skipped.
+    fn at_body_end(&mut self) -> bool {
+        loop {
+            let Some(token) = self.peek() else {
+                return true;
+            };
+
+            if !token.is_closer() {
+                return false;
+            }
+
+            if self.open_closers.contains(&token.kind) {
+                return true;
+            }
+
+            self.record_error(PrepareErrorKind::UnbalancedBracket, token.span);
+            self.record_repair(
+                RepairKind::DroppedBracket,
+                token.span,
+                format!("dropped unmatched `{}`", self.slice(token.span)),
+            );
+            self.position += 1;
+        }
+    }
+
+    fn parse_array_elements(&mut self) -> Vec<LenientValue> {
+        let mut elements = Vec::new();
+
+        loop {
+            let separators = self.take_while(Token::is_separator);
+            self.keep_first_separator(&separators, elements.is_empty());
+
+            if self.at_body_end() {
+                break;
+            }
+
+            let element = self.parse_value();
+            elements.push(element);
+        }
+
+        elements
+    }
+
+    /// Parses the members of an object and applies the keyless policy, which may turn the
+    /// object into the value it wraps.
+    fn parse_object_body(&mut self) -> LenientKind {
+        let mut members = Vec::new();
+
+        loop {
+            let separators =
+                self.take_while(|token| token.is_separator() || token.kind == TokenKind::Colon);
+            self.keep_first_separator(&separators, members.is_empty());
+
+            if self.at_body_end() {
+                break;
+            }
+
+            let member = self.parse_member();
+            if !self.drop_invalid_literal_member(&member) {
+                members.push(member);
+            }
+        }
+
+        self.apply_keyless_policy(members)
+    }
+
+    /// Bracketed values without a key are handled by the keyless policy. Keyless members
+    /// left over after that, including orphan keys, are dropped when rendering.
+    fn apply_keyless_policy(&mut self, mut members: Vec<LenientMember>) -> LenientKind {
+        let is_keyless_container = |member: &LenientMember| {
+            member.key.is_none()
+                && matches!(
+                    member.value.kind,
+                    LenientKind::Object(_) | LenientKind::Array(_)
+                )
+        };
+
+        let options = self.options;
+        match &options.keyless_policy {
+            KeylessPolicy::Unwrap if members.len() == 1 && is_keyless_container(&members[0]) => {
+                let keyless_value = members.remove(0).value;
+                self.record_repair(
+                    RepairKind::UnwrappedKeylessValue,
+                    keyless_value.span,
+                    "removed the braces around a value without a key",
+                );
+
+                return keyless_value.kind;
+            }
+            KeylessPolicy::SyntheticKey(name) => {
+                let keyless_members = members
+                    .iter_mut()
+                    .filter(|member| is_keyless_container(member));
+                for (index, member) in keyless_members.enumerate() {
+                    let synthetic_name = match index {
+                        0 => name.clone(),
+                        index => format!("{name}_{}", index + 1),
+                    };
+                    let span = Span::new(member.value.span.start, member.value.span.start);
+
+                    self.record_repair(
+                        RepairKind::AddedKey,
+                        span,
+                        format!("added the key `{synthetic_name}` to a value without a key"),
+                    );
+                    member.key = Some(LenientKey {
+                        name: synthetic_name,
+                        quoted: true,
+                        span,
+                    });
+                }
+            }
+            KeylessPolicy::Unwrap | KeylessPolicy::Error => {
+                let keyless_spans = members
+                    .iter()
+                    .filter(|member| is_keyless_container(member))
+                    .map(|member| member.value.span)
+                    .collect::<Vec<_>>();
+                for span in keyless_spans {
+                    self.record_error(PrepareErrorKind::KeylessValue, span);
+                }
+            }
+        }
+
+        let dropped_spans = members
+            .iter()
+            .filter(|member| member.key.is_none())
+            .map(|member| member.value.span)
+            .collect::<Vec<_>>();
+        for span in dropped_spans {
+            self.record_repair(
+                RepairKind::DroppedMember,
+                span,
+                format!("dropped `{}`, which has no key", self.slice(span)),
+            );
+        }
+
+        LenientKind::Object(members)
+    }
+
+    /// Parses the inside of a PHP array, whose members may or may not have keys.
+    fn parse_php_array_body(&mut self) -> LenientKind {
+        let mut members = Vec::new();
+
+        loop {
+            let separators = self.take_while(Token::is_separator);
+            self.keep_first_separator(&separators, members.is_empty());
+
+            if self.at_body_end() {
+                break;
+            }
+
+            let member = if self.at_keyed_member() {
+                self.parse_member()
+            } else {
+                LenientMember {
+                    key: None,
+                    value: self.parse_value(),
+                }
+            };
+            members.push(member);
+        }
+
+        php_array_kind(members)
+    }
+
+    fn parse_member(&mut self) -> LenientMember {
+        let key = if self.at_container() {
+            let container = self.parse_container();
+            match self.wrapped_key(container) {
+                Ok(key) => key,
+                Err(value) => {
+                    self.drop_trailing_content();
+                    return LenientMember { key: None, value };
+                }
+            }
+        } else {
+            let mut run = self.collect_run(true);
+            if !self
+                .peek()
+                .is_some_and(|token| token.kind == TokenKind::Colon)
+            {
+                let value = self.scalar_from_run(run);
+                self.record_error(PrepareErrorKind::OrphanKey, value.span);
+
+                return LenientMember { key: None, value };
+            }
+
+            let is_quoted = run.len() == 1 && self.tokens[run.start].is_string();
+            if self.options.namespaced_keys && !is_quoted {
+                run.end = self.namespaced_key_end(run.end);
+                self.position = run.end;
+            }
+            self.key_from_run(run)
+        };
+
+        self.position += 1;
+        let value = self.parse_value();
+
+        if !key.quoted {
+            self.record_repair(
+                RepairKind::QuotedKey,
+                key.span,
+                format!("quoted key `{}`", key.name),
+            );
+        }
+
+        if value.kind == LenientKind::Empty {
+            self.record_error(PrepareErrorKind::MissingValue, value.span);
+            self.record_repair(
+                RepairKind::FilledMissingValue,
+                value.span,
+                format!("filled the missing value of `{}`", key.name),
+            );
+        }
+
+        LenientMember {
+            key: Some(key),
+            value,
+        }
+    }
+
+    /// A dictionary key wrapped like `AnyHashable("a")` in Swift, which reaches here as the
+    /// unwrapped argument of the call. Anything else is handed back as a value without a key.
+    fn wrapped_key(&self, value: LenientValue) -> Result<LenientKey, LenientValue> {
+        let is_key = self.syntax.dictionaries
+            && self
+                .peek()
+                .is_some_and(|token| token.kind == TokenKind::Colon);
+
+        match value.kind {
+            LenientKind::String(name) | LenientKind::Bare(name) | LenientKind::Number(name)
+                if is_key =>
+            {
+                Ok(LenientKey {
+                    name,
+                    quoted: true,
+                    span: value.span,
+                })
+            }
+            _ => Err(value),
+        }
+    }
+
+    /// With the `namespaced_keys` option, unquoted keys may be colon-namespaced, as in
+    /// `input:max_adr: 205`. When the colon after
+    /// the key is directly followed by more key text, the key is extended up to the first later
+    /// colon that is followed by whitespace. Without such a colon, the key ends at the first one,
+    /// so `time:12:30` still has the key `time`. Returns the index of the colon to split at.
+    fn namespaced_key_end(&self, colon_index: usize) -> usize {
+        if !self.options.namespaced_keys {
+            return colon_index;
+        }
+
+        let mut index = colon_index;
+
+        while let (Some(colon), Some(next)) = (self.tokens.get(index), self.tokens.get(index + 1)) {
+            let is_tight = colon.kind == TokenKind::Colon
+                && next.kind == TokenKind::Text
+                && next.span.start == colon.span.end;
+            if !is_tight {
+                break;
+            }
+
+            match self.tokens.get(index + 2) {
+                Some(following) if following.kind == TokenKind::Colon => {
+                    let is_spaced = self
+                        .tokens
+                        .get(index + 3)
+                        .is_none_or(|value| value.span.start > following.span.end);
+                    if is_spaced {
+                        return index + 2;
+                    }
+                    index += 2;
+                }
+                _ => break,
+            }
+        }
+
+        colon_index
+    }
+
+    fn parse_value(&mut self) -> LenientValue {
+        let Some(first) = self.peek() else {
+            return LenientValue::new(LenientKind::Empty, self.empty_span_here());
+        };
+
+        if first.is_separator() || first.is_closer() {
+            return LenientValue::new(LenientKind::Empty, self.empty_span_here());
+        }
+
+        if self.at_container() {
+            let container = self.parse_container();
+            self.drop_trailing_content();
+
+            return container;
+        }
+
+        let run = self.collect_run(false);
+
+        self.scalar_from_run(run)
+    }
+
+    /// Drops what follows a container up to the end of its element or member.
+    fn drop_trailing_content(&mut self) {
+        let trailing_run = self.collect_run(false);
+
+        if !trailing_run.is_empty() {
+            let trailing_span = self.run_span(&trailing_run);
+            self.record_error(PrepareErrorKind::UnexpectedContent, trailing_span);
+            self.record_repair(
+                RepairKind::DroppedContent,
+                trailing_span,
+                format!("dropped `{}` after the value", self.slice(trailing_span)),
+            );
+        }
+    }
+
+    /// Consumes tokens up to the next separator, colon (if `stop_at_colon`) or closer
+    /// outside of any brackets opened within the run itself.
+    fn collect_run(&mut self, stop_at_colon: bool) -> Range<usize> {
+        let start = self.position;
+        let mut depth = 0_usize;
+
+        while let Some(token) = self.peek() {
+            let is_stop = token.is_separator()
+                || token.is_closer()
+                || (stop_at_colon && token.kind == TokenKind::Colon);
+
+            if depth == 0 && is_stop {
+                break;
+            }
+
+            if token.is_opener() {
+                depth += 1;
+            } else if token.is_closer() {
+                depth -= 1;
+            }
+
+            self.position += 1;
+        }
+
+        start..self.position
+    }
+
+    fn run_span(&self, run: &Range<usize>) -> Span {
+        if run.is_empty() {
+            return self.empty_span_here();
+        }
+
+        self.tokens[run.start]
+            .span
+            .to(self.tokens[run.end - 1].span)
+    }
+
+    /// The text of a run with any comments inside it cut out, so `a /* note */ b` reads `a b`.
+    fn run_text(&self, span: Span) -> Cow<'a, str> {
+        let inner_comments = self
+            .comments
+            .iter()
+            .filter(|comment| span.start < comment.start && comment.end < span.end)
+            .collect::<Vec<_>>();
+
+        if inner_comments.is_empty() {
+            return Cow::Borrowed(self.slice(span));
+        }
+
+        let mut pieces = Vec::with_capacity(inner_comments.len() + 1);
+        let mut piece_start = span.start;
+        for comment in inner_comments {
+            pieces.push(self.input[piece_start..comment.start].trim());
+            piece_start = comment.end;
+        }
+        pieces.push(self.input[piece_start..span.end].trim());
+
+        Cow::Owned(
+            pieces
+                .into_iter()
+                .filter(|piece| !piece.is_empty())
+                .join(" "),
+        )
+    }
+
+    fn key_from_run(&mut self, run: Range<usize>) -> LenientKey {
+        let span = self.run_span(&run);
+        let run_text = self.run_text(span);
+        let raw = run_text.as_ref();
+        self.run_spans.push(span);
+
+        let lenient_key = if run.len() == 1 && self.tokens[run.start].is_string() {
+            LenientKey {
+                name: decode_string(raw, &self.syntax),
+                quoted: true,
+                span,
+            }
+        } else if self.syntax.escapes == Escapes::JavaScript {
+            LenientKey {
+                name: decode_identifier(raw),
+                quoted: false,
+                span,
+            }
+        } else {
+            LenientKey {
+                name: raw.to_string(),
+                quoted: false,
+                span,
+            }
+        };
+
+        lenient_key
+    }
+
+    fn scalar_from_run(&mut self, run: Range<usize>) -> LenientValue {
+        let span = self.run_span(&run);
+        let run_text = self.run_text(span);
+        let raw = run_text.as_ref();
+        self.run_spans.push(span);
+
+        let kind = match raw {
+            "" => LenientKind::Empty,
+            raw if run.len() == 1
+                && self.tokens[run.start].is_string()
+                && self.syntax.multiline_strings
+                && raw.starts_with("'''") =>
+            {
+                let line_start = self.input[..span.start]
+                    .rfind('\n')
+                    .map_or(0, |newline| newline + 1);
+                let indent = self.input[line_start..span.start].chars().count();
+                LenientKind::String(decode_multiline_string(raw, indent))
+            }
+            raw if run.len() == 1 && self.tokens[run.start].is_string() => {
+                LenientKind::String(decode_string(raw, &self.syntax))
+            }
+            raw => match self.dialect_scalar_kind(&run, raw) {
+                Some(kind) => kind,
+                None if self.options.value_mode == ValueMode::Typed && is_invalid_literal(raw) => {
+                    match self.options.invalid_literal_policy {
+                        InvalidLiteralPolicy::Null | InvalidLiteralPolicy::DropKey => {
+                            LenientKind::Null
+                        }
+                        InvalidLiteralPolicy::String => LenientKind::Bare(raw.to_string()),
+                    }
+                }
+                None => scalar_kind(raw),
+            },
+        };
+
+        LenientValue::new(kind, span)
+    }
+
+    /// Scalars spelled in a way only some dialects understand.
+    fn dialect_scalar_kind(&self, run: &Range<usize>, raw: &str) -> Option<LenientKind> {
+        if self.syntax.null_literals.contains(&raw) {
+            return Some(LenientKind::Null);
+        }
+
+        if self.syntax.radix_numbers {
+            if let Some(number) = radix_number(raw) {
+                return Some(LenientKind::Number(number.to_string()));
+            }
+        }
+
+        if self.syntax.concatenation {
+            return self.concatenated_string(run);
+        }
+
+        None
+    }
+
+    /// Joins a run like `'a' + 'b' + 1` into one string. Every other token must be a `+`,
+    /// and the operands strings or numbers, at least one of them a string.
+    fn concatenated_string(&self, run: &Range<usize>) -> Option<LenientKind> {
+        let tokens = &self.tokens[run.clone()];
+        if tokens.len() < 3 || tokens.len() % 2 == 0 {
+            return None;
+        }
+
+        let mut concatenated = String::new();
+        for (index, token) in tokens.iter().enumerate() {
+            let raw = self.slice(token.span);
+            match token.kind {
+                TokenKind::Text if index % 2 == 1 && raw == "+" => {}
+                TokenKind::String if index % 2 == 0 => {
+                    concatenated.push_str(&decode_string(raw, &self.syntax));
+                }
+                TokenKind::Text if index % 2 == 0 && raw.parse::<f64>().is_ok() => {
+                    concatenated.push_str(raw);
+                }
+                _ => return None,
+            }
+        }
+
+        let has_string = tokens.iter().any(|token| token.kind == TokenKind::String);
+        has_string.then_some(LenientKind::String(concatenated))
+    }
+
+    /// With [`InvalidLiteralPolicy::DropKey`], members whose value was an invalid literal are
+    /// dropped from objects. Returns whether the member was dropped.
+    fn drop_invalid_literal_member(&mut self, member: &LenientMember) -> bool {
+        let Some(key) = &member.key else {
+            return false;
+        };
+
+        let is_dropped = self.options.value_mode == ValueMode::Typed
+            && self.options.invalid_literal_policy == InvalidLiteralPolicy::DropKey
+            && member.value.kind == LenientKind::Null
+            && is_invalid_literal(self.slice(member.value.span));
+
+        if is_dropped {
+            self.record_repair(
+                RepairKind::DroppedMember,
+                key.span.to(member.value.span),
+                format!(
+                    "dropped `{}`, whose value `{}` has no JSON equivalent",
+                    key.name,
+                    self.slice(member.value.span)
+                ),
+            );
+        }
+
+        is_dropped
+    }
+}
+
+/// PHP arrays are lists when their keys run 0, 1, 2, ... in order, and objects otherwise.
+/// Members without a key take the next integer key, as they do in PHP.
+fn php_array_kind(mut members: Vec<LenientMember>) -> LenientKind {
+    let mut next_index = 0_i64;
+
+    for member in &mut members {
+        if let Some(key) = &member.key {
+            if let Ok(index) = key.name.parse::<i64>() {
+                next_index = next_index.max(index + 1);
+            }
+            continue;
+        }
+
+        member.key = Some(LenientKey {
+            name: next_index.to_string(),
+            quoted: false,
+            span: Span::new(member.value.span.start, member.value.span.start),
+        });
+        next_index += 1;
+    }
+
+    let is_list = members.iter().enumerate().all(|(index, member)| {
+        member
+            .key
+            .as_ref()
+            .is_some_and(|key| key.name == index.to_string())
+    });
+
+    let php_array_kind = if is_list {
+        LenientKind::Array(members.into_iter().map(|member| member.value).collect())
+    } else {
+        LenientKind::Object(members)
+    };
+
+    php_array_kind
+}
+
+/// The member for a `(key, value)` pair of a Python mapping built from pairs. Anything else
+/// becomes a member without a key, which is dropped.
+fn pair_member(pair: LenientValue) -> LenientMember {
+    let LenientKind::Array(mut elements) = pair.kind else {
+        return LenientMember {
+            key: None,
+            value: pair,
+        };
+    };
+
+    let key =
+        match elements.first().map(|key| &key.kind) {
+            Some(
+                LenientKind::String(name) | LenientKind::Bare(name) | LenientKind::Number(name),
+            ) if elements.len() == 2 => Some(LenientKey {
+                name: name.clone(),
+                quoted: true,
+                span: elements[0].span,
+            }),
+            _ => None,
+        };
+
+    match key {
+        Some(key) => LenientMember {
+            key: Some(key),
+            value: elements.remove(1),
+        },
+        None => LenientMember {
+            key: None,
+            value: LenientValue::new(LenientKind::Array(elements), pair.span),
+        },
+    }
+}
+
+/// Reads `0x`, `0o` and `0b` integers, with an optional sign and `_` digit separators.
+fn radix_number(raw: &str) -> Option<i128> {
+    let (is_negative, unsigned) = match raw.as_bytes().first() {
+        Some(b'-') => (true, &raw[1..]),
+        Some(b'+') => (false, &raw[1..]),
+        _ => (false, raw),
+    };
+
+    let radix = match unsigned.get(..2)? {
+        "0x" | "0X" => 16,
+        "0o" | "0O" => 8,
+        "0b" | "0B" => 2,
+        _ => return None,
+    };
+
+    let digits = unsigned[2..].replace('_', "");
+    let magnitude = i128::from_str_radix(&digits, radix).ok()?;
+
+    let radix_number = if is_negative { -magnitude } else { magnitude };
+
+    Some(radix_number)
+}
+
+/// Literals from other languages that look like values but have no JSON spelling, including
+/// numbers too large to be finite.
+fn is_invalid_literal(raw: &str) -> bool {
+    let lowercase = raw.to_lowercase();
+
+    let is_invalid_literal = match lowercase.as_str() {
+        "none" | "undefined" => true,
+        lowercase => lowercase
+            .parse::<f64>()
+            .is_ok_and(|number| !number.is_finite()),
+    };
+
+    is_invalid_literal
+}
+
+/// Whether a number is spelled with a leading zero, as in `007`. No dialect reads such a
+/// number as decimal, and the zeros of a zip code or an ID matter, so it stays a string.
+fn has_leading_zero(number: &str) -> bool {
+    let unsigned = number.strip_prefix(['-', '+']).unwrap_or(number);
+    let mut bytes = unsigned.bytes();
+
+    bytes.next() == Some(b'0') && bytes.next().is_some_and(|byte| byte.is_ascii_digit())
+}
+
+fn scalar_kind(raw: &str) -> LenientKind {
+    let lowercase = raw.to_lowercase();
+
+    let scalar_kind = match lowercase.as_str() {
+        lowercase if lowercase.parse::<bool>().is_ok() => LenientKind::Bool(lowercase == "true"),
+        "null" => LenientKind::Null,
+        lowercase
+            if (lowercase.parse::<f64>().is_ok()
+                || lowercase.parse::<u64>().is_ok()
+                || lowercase.parse::<i64>().is_ok())
+                && !has_leading_zero(lowercase) =>
+        {
+            LenientKind::Number(raw.to_string())
+        }
+        _ => LenientKind::Bare(raw.to_string()),
+    };
+
+    scalar_kind
+}
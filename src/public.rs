@@ -1,6 +1,17 @@
-pub mod parse_json_string;
-pub mod parse_stringified_json_string;
+pub mod deserialize_error;
+pub mod lenient_deserializer;
+pub mod lenient_value;
+pub mod parse_lenient_json_string;
+pub mod prepare_error;
+pub mod prepare_fragment;
 pub mod prepare_json_array;
 pub mod prepare_json_string;
+pub mod prepare_options;
+pub mod prepare_to_value;
+pub mod prepare_with_report;
+pub mod preparer;
+pub mod repair;
+pub mod repairing_reader;
 pub mod stringified_json_array;
 pub mod stringified_json_string;
+pub mod stringified_to_value;
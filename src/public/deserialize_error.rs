@@ -0,0 +1,70 @@
+use std::fmt;
+
+use crate::public::prepare_error::{PrepareError, PrepareErrorKind};
+
+/// Where in the input a problem was found. `line` and `column` are 1-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Error returned by [`crate::from_str`] and [`crate::LenientDeserializer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeserializeError {
+    pub message: String,
+    pub position: Option<Position>,
+    /// The kind of the [`PrepareError`] this error comes from, or `None` when the input was
+    /// read but did not fit the target type.
+    pub kind: Option<PrepareErrorKind>,
+}
+
+impl DeserializeError {
+    pub(crate) fn or_position(mut self, position: Position) -> Self {
+        if self.position.is_none() {
+            self.position = Some(position);
+        }
+
+        self
+    }
+}
+
+impl From<PrepareError> for DeserializeError {
+    fn from(prepare_error: PrepareError) -> Self {
+        Self {
+            message: prepare_error.kind.to_string(),
+            position: Some(Position {
+                offset: prepare_error.offset,
+                line: prepare_error.line,
+                column: prepare_error.column,
+            }),
+            kind: Some(prepare_error.kind),
+        }
+    }
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(
+                f,
+                "{} at line {}, column {} (byte {})",
+                self.message, position.line, position.column, position.offset
+            ),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl serde::de::Error for DeserializeError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Self {
+            message: message.to_string(),
+            position: None,
+            kind: None,
+        }
+    }
+}
@@ -0,0 +1,535 @@
+use std::borrow::Cow;
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    Unexpected, VariantAccess, Visitor,
+};
+
+use crate::{
+    helpers::line_column,
+    public::{
+        deserialize_error::{DeserializeError, Position},
+        lenient_value::{LenientKind, LenientMember, LenientValue},
+        preparer::Preparer,
+    },
+};
+
+/// Deserializes sloppy JSON with the default options. See [`Preparer::from_str`] for other
+/// dialects and options.
+pub fn from_str<T: DeserializeOwned>(original_str: &str) -> Result<T, DeserializeError> {
+    let deserialized = Preparer::default().from_str(original_str)?;
+
+    Ok(deserialized)
+}
+
+/// Deserializes a [`LenientValue`], coercing scalars to whatever the target type asks for:
+/// an unquoted `17` becomes a number for a `u32` field and `"17"` for a `String` field.
+#[derive(Clone, Copy, Debug)]
+pub struct LenientDeserializer<'a> {
+    lenient_value: &'a LenientValue,
+    input: &'a str,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Hint {
+    Any,
+    Bool,
+    Signed,
+    Unsigned,
+    Float,
+    Str,
+    Option,
+    Unit,
+    Seq,
+    Map,
+    Enum,
+}
+
+impl<'a> LenientDeserializer<'a> {
+    /// `input` is the text `lenient_value` was parsed from, used to report error positions.
+    pub fn new(lenient_value: &'a LenientValue, input: &'a str) -> Self {
+        Self {
+            lenient_value,
+            input,
+        }
+    }
+
+    fn position(&self) -> Position {
+        let offset = self.lenient_value.span.start;
+        let (line, column) = line_column(self.input, offset);
+
+        Position {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(
+            self.lenient_value.kind,
+            LenientKind::Null | LenientKind::Empty
+        )
+    }
+
+    fn scalar_text(&self) -> Option<Cow<'a, str>> {
+        let scalar_text = match &self.lenient_value.kind {
+            LenientKind::String(text) | LenientKind::Bare(text) | LenientKind::Number(text) => {
+                Cow::Borrowed(text.as_str())
+            }
+            LenientKind::Bool(boolean) => Cow::Owned(boolean.to_string()),
+            LenientKind::Null => Cow::Borrowed("null"),
+            LenientKind::Empty => Cow::Borrowed(""),
+            LenientKind::Object(_) | LenientKind::Array(_) => return None,
+        };
+
+        Some(scalar_text)
+    }
+
+    fn unexpected(&self) -> Unexpected<'_> {
+        let unexpected = match &self.lenient_value.kind {
+            LenientKind::Object(_) => Unexpected::Map,
+            LenientKind::Array(_) => Unexpected::Seq,
+            LenientKind::String(text) | LenientKind::Bare(text) => Unexpected::Str(text),
+            LenientKind::Number(_) => Unexpected::Other("number"),
+            LenientKind::Bool(boolean) => Unexpected::Bool(*boolean),
+            LenientKind::Null | LenientKind::Empty => Unexpected::Unit,
+        };
+
+        unexpected
+    }
+
+    fn deserialize_hinted<'de, V: Visitor<'de>>(
+        self,
+        hint: Hint,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError> {
+        let number_text = self
+            .scalar_text()
+            .filter(|_| !matches!(self.lenient_value.kind, LenientKind::Bool(_)))
+            .map(|text| text.trim().to_lowercase());
+
+        let deserialized = match (hint, &self.lenient_value.kind) {
+            (Hint::Option, _) if self.is_null() => visitor.visit_none(),
+            (Hint::Option, _) => visitor.visit_some(self),
+            (Hint::Unit | Hint::Any, _) if self.is_null() => visitor.visit_unit(),
+            (Hint::Seq | Hint::Any, LenientKind::Array(elements)) => {
+                visitor.visit_seq(LenientSeqAccess {
+                    elements: elements.iter(),
+                    input: self.input,
+                })
+            }
+            (Hint::Map | Hint::Any, LenientKind::Object(members)) => {
+                visitor.visit_map(LenientMapAccess {
+                    members: members.iter(),
+                    pending_value: None,
+                    input: self.input,
+                })
+            }
+            (Hint::Enum, LenientKind::String(variant) | LenientKind::Bare(variant)) => {
+                visitor.visit_enum(variant.as_str().into_deserializer())
+            }
+            (Hint::Enum, LenientKind::Object(members)) => match members.as_slice() {
+                [LenientMember {
+                    key: Some(key),
+                    value,
+                }] => visitor.visit_enum(LenientEnumAccess {
+                    variant: &key.name,
+                    value: LenientDeserializer::new(value, self.input),
+                }),
+                _ => Err(de::Error::invalid_value(self.unexpected(), &visitor)),
+            },
+            (Hint::Bool | Hint::Any, LenientKind::Bool(boolean)) => visitor.visit_bool(*boolean),
+            (Hint::Bool, _) => match number_text.as_deref().map(str::parse::<bool>) {
+                Some(Ok(boolean)) => visitor.visit_bool(boolean),
+                _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+            },
+            (Hint::Signed, _) => match number_text.as_deref().map(str::parse::<i64>) {
+                Some(Ok(signed)) => visitor.visit_i64(signed),
+                _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+            },
+            (Hint::Unsigned, _) => match number_text.as_deref().map(str::parse::<u64>) {
+                Some(Ok(unsigned)) => visitor.visit_u64(unsigned),
+                _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+            },
+            (Hint::Float, _) => match number_text.as_deref().map(str::parse::<f64>) {
+                Some(Ok(float)) => visitor.visit_f64(float),
+                _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+            },
+            (Hint::Any, LenientKind::Number(number)) => {
+                let lowercase = number.to_lowercase();
+
+                if let Ok(unsigned) = lowercase.parse::<u64>() {
+                    visitor.visit_u64(unsigned)
+                } else if let Ok(signed) = lowercase.parse::<i64>() {
+                    visitor.visit_i64(signed)
+                } else {
+                    match lowercase.parse::<f64>() {
+                        Ok(float) => visitor.visit_f64(float),
+                        Err(_) => visitor.visit_str(number),
+                    }
+                }
+            }
+            (Hint::Str | Hint::Any, _) => match self.scalar_text() {
+                Some(text) => visitor.visit_str(&text),
+                None => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+            },
+            _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+        };
+
+        deserialized.map_err(|error| error.or_position(self.position()))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for LenientDeserializer<'_> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Any, visitor)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Bool, visitor)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Signed, visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Signed, visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Signed, visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Signed, visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Unsigned, visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Unsigned, visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Unsigned, visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Unsigned, visitor)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Float, visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Float, visitor)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Str, visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Str, visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Str, visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Str, visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Str, visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Option, visitor)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Unit, visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Unit, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Seq, visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Seq, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Seq, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Map, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Map, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Enum, visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Str, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct LenientSeqAccess<'a> {
+    elements: std::slice::Iter<'a, LenientValue>,
+    input: &'a str,
+}
+
+impl<'de> SeqAccess<'de> for LenientSeqAccess<'_> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        let Some(element) = self.elements.next() else {
+            return Ok(None);
+        };
+
+        let deserialized = seed.deserialize(LenientDeserializer::new(element, self.input))?;
+
+        Ok(Some(deserialized))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.elements.len())
+    }
+}
+
+struct LenientMapAccess<'a> {
+    members: std::slice::Iter<'a, LenientMember>,
+    pending_value: Option<&'a LenientValue>,
+    input: &'a str,
+}
+
+impl<'de> MapAccess<'de> for LenientMapAccess<'_> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self
+            .members
+            .by_ref()
+            .find_map(|member| Some((member.key.as_ref()?, &member.value)))
+        else {
+            return Ok(None);
+        };
+        self.pending_value = Some(value);
+
+        let key_value = LenientValue::new(LenientKind::String(key.name.clone()), key.span);
+        let deserialized = seed.deserialize(LenientDeserializer::new(&key_value, self.input))?;
+
+        Ok(Some(deserialized))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let Some(value) = self.pending_value.take() else {
+            return Err(de::Error::custom("value requested before its key"));
+        };
+
+        seed.deserialize(LenientDeserializer::new(value, self.input))
+    }
+}
+
+struct LenientEnumAccess<'a> {
+    variant: &'a str,
+    value: LenientDeserializer<'a>,
+}
+
+impl<'de, 'a> EnumAccess<'de> for LenientEnumAccess<'a> {
+    type Error = DeserializeError;
+    type Variant = LenientDeserializer<'a>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant_deserializer: de::value::StrDeserializer<'_, DeserializeError> =
+            self.variant.into_deserializer();
+        let variant = seed.deserialize(variant_deserializer)?;
+
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for LenientDeserializer<'_> {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Seq, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_hinted(Hint::Map, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use crate::{from_str, PrepareErrorKind};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Device {
+        unit_id: u32,
+        label: String,
+        code: String,
+        enabled: bool,
+        scale: Option<f64>,
+        groups: Vec<String>,
+    }
+
+    #[test]
+    fn sloppy_object_into_struct() {
+        let original_str = r#"{\n unit_id: 17; label: Pump 1; code: 17, enabled: TRUE, scale: none, groups: [a, "b, c",], }"#;
+
+        let device = from_str::<Device>(original_str).unwrap();
+
+        let expected_device = Device {
+            unit_id: 17,
+            label: "Pump 1".to_string(),
+            code: "17".to_string(),
+            enabled: true,
+            scale: None,
+            groups: vec!["a".to_string(), "b, c".to_string()],
+        };
+
+        assert_eq!(device, expected_device);
+    }
+
+    #[test]
+    fn quoted_numbers_coerce_to_numbers() {
+        let original_str = r#"[{"Foo1": "19"}, {"Foo1": 10}]"#;
+
+        let maps = from_str::<Vec<HashMap<String, u8>>>(original_str).unwrap();
+
+        assert_eq!(maps[0]["Foo1"], 19);
+        assert_eq!(maps[1]["Foo1"], 10);
+    }
+
+    #[test]
+    fn enums_from_bare_words_and_single_key_objects() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        enum State {
+            Idle,
+            Running { speed: u16 },
+        }
+
+        let states = from_str::<Vec<State>>("[Idle, {Running: {speed: 1200}}]").unwrap();
+
+        assert_eq!(states, vec![State::Idle, State::Running { speed: 1200 }]);
+    }
+
+    #[test]
+    fn errors_carry_the_position_of_the_value() {
+        let original_str = "{\n  unit_id: pump,\n}";
+
+        let error = from_str::<HashMap<String, u32>>(original_str).unwrap_err();
+        let position = error.position.unwrap();
+
+        assert_eq!(
+            (position.offset, position.line, position.column),
+            (13, 2, 12)
+        );
+    }
+
+    #[test]
+    fn parse_errors_are_reported() {
+        let error = from_str::<Vec<u32>>("[1, 2").unwrap_err();
+
+        assert_eq!(error.kind, Some(PrepareErrorKind::UnbalancedBracket));
+        assert_eq!(
+            error.message,
+            PrepareErrorKind::UnbalancedBracket.to_string()
+        );
+
+        let error = from_str::<Vec<u32>>("[1, x]").unwrap_err();
+
+        assert_eq!(error.kind, None);
+    }
+}
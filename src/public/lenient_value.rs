@@ -0,0 +1,94 @@
+/// Byte range `start..end` of the original input a node was read from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    #[must_use]
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn len(self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// A value as understood by the lenient parser, before it is rendered as JSON.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LenientValue {
+    pub kind: LenientKind,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LenientKind {
+    Object(Vec<LenientMember>),
+    Array(Vec<LenientValue>),
+    /// A quoted string, with its escape sequences decoded.
+    String(String),
+    /// A number, exactly as written in the input.
+    Number(String),
+    Bool(bool),
+    Null,
+    /// Unquoted text that is not a literal; rendered as a string.
+    Bare(String),
+    /// A value position with nothing in it, such as `"key": ,`.
+    Empty,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LenientMember {
+    /// `None` when the object holds a value without a key, such as `{[1, 2]}`.
+    pub key: Option<LenientKey>,
+    pub value: LenientValue,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LenientKey {
+    pub name: String,
+    pub quoted: bool,
+    pub span: Span,
+}
+
+impl LenientValue {
+    pub fn new(kind: LenientKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn as_object(&self) -> Option<&[LenientMember]> {
+        match &self.kind {
+            LenientKind::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[LenientValue]> {
+        match &self.kind {
+            LenientKind::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Looks up the first member named `key` when this value is an object.
+    pub fn get(&self, key: &str) -> Option<&LenientValue> {
+        let member = self.as_object()?.iter().find(|member| {
+            member
+                .key
+                .as_ref()
+                .is_some_and(|member_key| member_key.name == key)
+        })?;
+
+        Some(&member.value)
+    }
+}
@@ -0,0 +1,78 @@
+use crate::public::{lenient_value::LenientValue, preparer::Preparer};
+
+/// Parses sloppy JSON with the default options. See [`Preparer::parse_lenient`] for other
+/// dialects and options.
+pub fn parse_lenient_json_string(original_str: &str) -> LenientValue {
+    let lenient_value = Preparer::default().parse_lenient(original_str);
+
+    lenient_value
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_lenient_json_string, LenientKind, Span};
+
+    #[test]
+    fn spans_point_into_the_input() {
+        let original_str = r#"{label: "Pump 1", "groups": [a, 5]}"#;
+
+        let lenient_value = parse_lenient_json_string(original_str);
+
+        assert_eq!(lenient_value.span, Span::new(0, original_str.len()));
+
+        let members = lenient_value.as_object().unwrap();
+        let label_key = members[0].key.as_ref().unwrap();
+        assert_eq!(label_key.name, "label");
+        assert!(!label_key.quoted);
+        assert_eq!(
+            &original_str[label_key.span.start..label_key.span.end],
+            "label"
+        );
+
+        let label = lenient_value.get("label").unwrap();
+        assert_eq!(label.kind, LenientKind::String("Pump 1".to_string()));
+        assert_eq!(
+            &original_str[label.span.start..label.span.end],
+            r#""Pump 1""#
+        );
+
+        let groups = lenient_value.get("groups").unwrap().as_array().unwrap();
+        assert_eq!(groups[0].kind, LenientKind::Bare("a".to_string()));
+        assert_eq!(groups[1].kind, LenientKind::Number("5".to_string()));
+        assert_eq!(&original_str[groups[1].span.start..groups[1].span.end], "5");
+    }
+
+    #[test]
+    fn literals_and_escapes() {
+        let original_str = r#"[true, NULL, "a \"quoted\" é", , unquoted text]"#;
+
+        let lenient_value = parse_lenient_json_string(original_str);
+
+        let kinds = lenient_value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|element| element.kind.clone())
+            .collect::<Vec<_>>();
+
+        let expected_kinds = vec![
+            LenientKind::Bool(true),
+            LenientKind::Null,
+            LenientKind::String("a \"quoted\" é".to_string()),
+            LenientKind::Bare("unquoted text".to_string()),
+        ];
+
+        assert_eq!(kinds, expected_kinds);
+    }
+
+    #[test]
+    fn unclosed_containers_are_closed_at_the_end() {
+        let original_str = r#"{"a": [1, 2}"#;
+
+        let lenient_value = parse_lenient_json_string(original_str);
+
+        let a = lenient_value.get("a").unwrap().as_array().unwrap();
+        assert_eq!(a.len(), 2);
+        assert_eq!(lenient_value.span, Span::new(0, original_str.len()));
+    }
+}
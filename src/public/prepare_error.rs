@@ -0,0 +1,77 @@
+use std::fmt;
+
+use crate::helpers::line_column;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PrepareErrorKind {
+    /// The input holds no value at all.
+    EmptyInput,
+    /// A `"` string runs to the end of the input without a closing quote.
+    UnterminatedString,
+    /// A bracket or brace is never closed, or a closer has no matching opener.
+    UnbalancedBracket,
+    /// An object member has no `:` separating a key from a value.
+    OrphanKey,
+    /// An object member has a key and a `:` but no value.
+    MissingValue,
+    /// An object holds a bracketed value that has no key, such as `{[1, 2]}`.
+    KeylessValue,
+    /// Content follows a complete value where only a separator was expected.
+    UnexpectedContent,
+    /// A value has no JSON representation, such as `None` or a non-finite number.
+    InvalidValue,
+}
+
+impl fmt::Display for PrepareErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            PrepareErrorKind::EmptyInput => "empty input",
+            PrepareErrorKind::UnterminatedString => "unterminated string",
+            PrepareErrorKind::UnbalancedBracket => "unbalanced bracket",
+            PrepareErrorKind::OrphanKey => "key without a value",
+            PrepareErrorKind::MissingValue => "missing value after `:`",
+            PrepareErrorKind::KeylessValue => "value without a key inside an object",
+            PrepareErrorKind::UnexpectedContent => "unexpected content after a value",
+            PrepareErrorKind::InvalidValue => "value has no JSON representation",
+        };
+
+        f.write_str(description)
+    }
+}
+
+/// Why an input could not be prepared, and where in the input that was found.
+///
+/// `offset` is a byte offset; `line` and `column` are 1-based, with the column counted
+/// in characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrepareError {
+    pub kind: PrepareErrorKind,
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl PrepareError {
+    pub(crate) fn new(kind: PrepareErrorKind, input: &str, offset: usize) -> Self {
+        let (line, column) = line_column(input, offset);
+
+        Self {
+            kind,
+            offset,
+            line,
+            column,
+        }
+    }
+}
+
+impl fmt::Display for PrepareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {} (byte {})",
+            self.kind, self.line, self.column, self.offset
+        )
+    }
+}
+
+impl std::error::Error for PrepareError {}
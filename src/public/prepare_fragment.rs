@@ -0,0 +1,107 @@
+use crate::{
+    helpers::JsonContext,
+    public::{prepare_error::PrepareError, prepare_options::PrepareOptions},
+};
+
+/// What part of a JSON document a fragment holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FragmentKind {
+    /// The inside of an object, such as `a: 1, b: 2`.
+    ObjectBody,
+    /// The inside of an array, such as `1, 2, 3`.
+    ArrayBody,
+    /// One complete value.
+    Value,
+}
+
+impl FragmentKind {
+    pub(crate) fn json_context(self) -> JsonContext {
+        let json_context = match self {
+            FragmentKind::ObjectBody => JsonContext::Object,
+            FragmentKind::ArrayBody => JsonContext::Array,
+            FragmentKind::Value => JsonContext::Value,
+        };
+
+        json_context
+    }
+}
+
+/// Prepares a fragment of the given kind and returns it as a complete JSON document, so an
+/// object body comes back wrapped in `{}` and an array body in `[]`.
+pub fn prepare_fragment(original_str: &str, fragment_kind: FragmentKind) -> String {
+    let prepared_string = PrepareOptions::new()
+        .build()
+        .prepare_fragment(original_str, fragment_kind);
+
+    prepared_string
+}
+
+pub fn try_prepare_fragment(
+    original_str: &str,
+    fragment_kind: FragmentKind,
+) -> Result<String, PrepareError> {
+    let prepared_string = PrepareOptions::new()
+        .build()
+        .try_prepare_fragment(original_str, fragment_kind)?;
+
+    Ok(prepared_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prepare_fragment, prepare_json_string, try_prepare_fragment, FragmentKind};
+
+    #[test]
+    fn object_body() {
+        let original_str = r#"unit_id: 5, "input:max_adr": 205; label: SOMETHING"#;
+
+        let prepared_str = prepare_fragment(original_str, FragmentKind::ObjectBody);
+
+        let expected_str = r#"{"unit_id": 5, "input:max_adr": 205, "label": "SOMETHING"}"#;
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn array_body_is_always_wrapped() {
+        assert_eq!(
+            prepare_fragment("1, b; true", FragmentKind::ArrayBody),
+            r#"[1, "b", true]"#
+        );
+        assert_eq!(
+            prepare_fragment("[1, 2]", FragmentKind::ArrayBody),
+            "[[1, 2]]"
+        );
+        assert_eq!(prepare_fragment("", FragmentKind::ArrayBody), "[]");
+    }
+
+    #[test]
+    fn value() {
+        let prepared_str = prepare_fragment("http://host:8080/path", FragmentKind::Value);
+
+        assert_eq!(prepared_str, r#""http://host:8080/path""#);
+    }
+
+    #[test]
+    fn empty_object_body_is_an_error() {
+        assert_eq!(prepare_fragment("", FragmentKind::ObjectBody), "{}");
+        assert!(try_prepare_fragment(" , ", FragmentKind::ObjectBody).is_err());
+    }
+
+    #[test]
+    fn brace_less_top_level_is_detected_as_object() {
+        let original_strs = [
+            ("a: 1, b: 2", r#"{"a": 1, "b": 2}"#),
+            ("a:1;b:x", r#"{"a": 1, "b": "x"}"#),
+            (r#""urn:x:y":1"#, r#"{"urn:x:y": 1}"#),
+            (r"\nlabel:\nlol", r#"{"label": "lol"}"#),
+            ("12:30", r#""12:30""#),
+            ("urn:x:y", r#""urn:x:y""#),
+            ("plain text", r#""plain text""#),
+        ];
+
+        for (original_str, expected_str) in original_strs {
+            assert_eq!(prepare_json_string(original_str), expected_str);
+        }
+    }
+}
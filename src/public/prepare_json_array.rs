@@ -1,30 +1,41 @@
-use crate::helpers::{content_str, ensure_array_wrapper, json_context, rewrap_string};
+use std::borrow::Cow;
 
-use super::parse_json_string::parse_json_string;
+use crate::public::{
+    prepare_error::PrepareError,
+    prepare_options::{PrepareOptions, TopLevel},
+    preparer::Preparer,
+};
+
+fn array_preparer() -> Preparer {
+    PrepareOptions::new().top_level(TopLevel::Array).build()
+}
 
 pub fn prepare_json_array(original_str: &str) -> String {
-    let trimmed_str = original_str
-        .trim_matches([' ', '\n', '\t', ',', ';', ':'])
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .trim_start_matches("\\n")
-        .trim_end_matches("\\n");
+    let prepared_string = array_preparer().prepare(original_str);
 
-    let array_input = ensure_array_wrapper(trimmed_str);
+    prepared_string
+}
 
-    let json_context = json_context(&array_input);
-    let content_str = content_str(json_context.clone(), &array_input);
+/// Returns the input unchanged when it is already a valid JSON array, and repairs it otherwise.
+pub fn prepare_json_array_cow(original_str: &str) -> Cow<'_, str> {
+    let prepared_string = array_preparer().prepare_cow(original_str);
 
-    let parsed_json_string = parse_json_string(&content_str, json_context.clone());
+    prepared_string
+}
 
-    let rewrapped_string = rewrap_string(&parsed_json_string, json_context);
+pub fn try_prepare_json_array(original_str: &str) -> Result<String, PrepareError> {
+    let prepared_string = array_preparer().try_prepare(original_str)?;
 
-    rewrapped_string
+    Ok(prepared_string)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::prepare_json_array;
+    use std::borrow::Cow;
+
+    use crate::{
+        prepare_json_array, prepare_json_array_cow, try_prepare_json_array, PrepareErrorKind,
+    };
 
     #[test]
     fn comma_inside_value() {
@@ -118,7 +129,66 @@ mod tests {
 
         let prepared_str = prepare_json_array(original_str);
 
-        let expected_str = r#"[{[{"unit_id": 5, "ec_id": 0, "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]}]"#.to_string();
+        let expected_str = r#"[{"unit_id": 5, "ec_id": 0, "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]"#.to_string();
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn try_prepare_json_array_wraps_objects() {
+        let original_str = "{a: 1}; {a: 2};";
+
+        let prepared_str = try_prepare_json_array(original_str).unwrap();
+
+        let expected_str = r#"[{"a": 1}, {"a": 2}]"#;
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn try_prepare_json_array_missing_value() {
+        let original_str = "[{a: 1}, {a: }]";
+
+        let error = try_prepare_json_array(original_str).unwrap_err();
+
+        assert_eq!(error.kind, PrepareErrorKind::MissingValue);
+        assert_eq!(error.offset, 13);
+    }
+
+    #[test]
+    fn prepare_json_array_cow_borrows_valid_arrays_only() {
+        let original_str = r#"[{"tag":"lol","groups":[]}]"#;
+        let prepared_str = prepare_json_array_cow(original_str);
+        assert!(matches!(prepared_str, Cow::Borrowed(borrowed) if borrowed == original_str));
+
+        let original_str = r#"{"tag":"lol"}"#;
+        let prepared_str = prepare_json_array_cow(original_str);
+        assert!(matches!(prepared_str, Cow::Owned(owned) if owned == r#"[{"tag": "lol"}]"#));
+    }
+
+    #[test]
+    fn separators_and_brackets_inside_strings_do_not_split_elements() {
+        let original_strs = [
+            (r#"["a, b", "c"]"#, r#"["a, b", "c"]"#),
+            (r#"["x;y"]"#, r#"["x;y"]"#),
+            (r#"["a]b", "c"]"#, r#"["a]b", "c"]"#),
+            (r#"["{[", "]}"; "c"]"#, r#"["{[", "]}", "c"]"#),
+            (r#"["say \"a, b\"", "c]"]"#, r#"["say \"a, b\"", "c]"]"#),
+            (r#"["ends with \\", "d"]"#, r#"["ends with \\", "d"]"#),
+        ];
+
+        for (original_str, expected_str) in original_strs {
+            assert_eq!(prepare_json_array(original_str), expected_str);
+        }
+    }
+
+    #[test]
+    fn free_text_labels_without_brackets() {
+        let original_str = r#""Pump 1, north"; "Valve [2]", "Tank {3}: \"main\"""#;
+
+        let prepared_str = prepare_json_array(original_str);
+
+        let expected_str = r#"["Pump 1, north", "Valve [2]", "Tank {3}: \"main\""]"#;
 
         assert_eq!(prepared_str, expected_str);
     }
@@ -1,35 +1,34 @@
-use crate::helpers::{content_str, json_context, rewrap_string};
+use std::borrow::Cow;
 
-use super::parse_json_string::parse_json_string;
+use crate::public::{prepare_error::PrepareError, prepare_options::PrepareOptions};
 
 pub fn prepare_json_string(original_str: &str) -> String {
-    let trimmed_str = original_str
-        .trim_matches([' ', '\n', '\t', ',', ';', ':'])
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .trim_start_matches("\\n")
-        .trim_end_matches("\\n");
+    let prepared_string = PrepareOptions::new().build().prepare(original_str);
 
-    let json_context = json_context(trimmed_str);
-
-    let content_str = content_str(json_context.clone(), trimmed_str);
+    prepared_string
+}
 
-    let content_str = content_str
-        .trim_matches([' ', '\n', '\t', ',', ';', ':'])
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .to_string();
+/// Returns the input unchanged when it is already valid JSON, and repairs it otherwise.
+pub fn prepare_json_string_cow(original_str: &str) -> Cow<'_, str> {
+    let prepared_string = PrepareOptions::new().build().prepare_cow(original_str);
 
-    let parsed_json_string = parse_json_string(&content_str, json_context.clone());
+    prepared_string
+}
 
-    let rewrapped_string = rewrap_string(&parsed_json_string, json_context);
+pub fn try_prepare_json_string(original_str: &str) -> Result<String, PrepareError> {
+    let prepared_string = PrepareOptions::new().build().try_prepare(original_str)?;
 
-    rewrapped_string
+    Ok(prepared_string)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::prepare_json_string;
+    use std::borrow::Cow;
+
+    use crate::{
+        prepare_json_string, prepare_json_string_cow, try_prepare_json_string, Dialect,
+        PrepareErrorKind, PrepareOptions,
+    };
 
     #[test]
     fn newline_chars1() {
@@ -149,8 +148,149 @@ mod tests {
 
         let prepared_str = prepare_json_string(original_str);
 
-        let expected_str = r#"{[{"unit_id": 5, "ec_id": 0, "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]}"#.to_string();
+        let expected_str = r#"[{"unit_id": 5, "ec_id": 0, "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]"#.to_string();
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn try_prepare_json_string_accepts_sloppy_input() {
+        let original_str = "{property1: lol; property2: 17,}";
+
+        let prepared_str = try_prepare_json_string(original_str).unwrap();
+
+        let expected_str = r#"{"property1": "lol", "property2": 17}"#;
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn try_prepare_json_string_unbalanced_bracket() {
+        let original_str = "{\n  \"a\": [1, 2\n}";
+
+        let error = try_prepare_json_string(original_str).unwrap_err();
+
+        assert_eq!(error.kind, PrepareErrorKind::UnbalancedBracket);
+        assert_eq!(error.offset, 15);
+        assert_eq!((error.line, error.column), (3, 1));
+    }
+
+    #[test]
+    fn try_prepare_json_string_orphan_key() {
+        let original_str = r#"{"a": 1, lonely, "b": 2}"#;
+
+        let error = try_prepare_json_string(original_str).unwrap_err();
+
+        assert_eq!(error.kind, PrepareErrorKind::OrphanKey);
+        assert_eq!((error.offset, error.line, error.column), (9, 1, 10));
+    }
+
+    #[test]
+    fn try_prepare_json_string_unterminated_string() {
+        let original_str = r#"{"a": "never closed}"#;
+
+        let error = try_prepare_json_string(original_str).unwrap_err();
+
+        assert_eq!(error.kind, PrepareErrorKind::UnterminatedString);
+        assert_eq!(error.offset, 6);
+    }
+
+    #[test]
+    fn try_prepare_json_string_empty_input() {
+        let error = try_prepare_json_string(" ;, \\n ").unwrap_err();
+
+        assert_eq!(error.kind, PrepareErrorKind::EmptyInput);
+    }
+
+    #[test]
+    fn prepare_json_string_cow_borrows_valid_json() {
+        let original_str = r#"{"label":"NaN","value":1E5, "missing": null}"#;
+
+        let prepared_str = prepare_json_string_cow(original_str);
+
+        assert!(matches!(prepared_str, Cow::Borrowed(borrowed) if borrowed == original_str));
+    }
+
+    #[test]
+    fn prepare_json_string_cow_repairs_invalid_json() {
+        let original_str = "{label: lol, groups: [],}";
+
+        let prepared_str = prepare_json_string_cow(original_str);
+
+        let expected_str = r#"{"label": "lol", "groups": []}"#;
+
+        assert!(matches!(prepared_str, Cow::Owned(owned) if owned == expected_str));
+    }
+
+    #[test]
+    fn escaped_quotes_do_not_end_strings_at_any_depth() {
+        let original_str = r#"{
+            label: "He said \"hi\", then left",
+            nested: {inner: [{"q": "a \"}], b\"; c"}, "ends with \\"]},
+            "key \"with\" quotes": 1
+        }"#;
+
+        let prepared_str = prepare_json_string(original_str);
+
+        let expected_str = r#"{"label": "He said \"hi\", then left", "nested": {"inner": [{"q": "a \"}], b\"; c"}, "ends with \\"]}, "key \"with\" quotes": 1}"#;
+
+        assert_eq!(prepared_str, expected_str);
+        assert_eq!(try_prepare_json_string(original_str).unwrap(), expected_str);
+    }
+
+    #[test]
+    fn colons_inside_keys() {
+        let original_str = r#"{"input:max_adr": 205, "urn:x:y":1, msg:error: disk full, time:12:30, url: http://host:80}"#;
+
+        let prepared_str = prepare_json_string(original_str);
+
+        let expected_str = r#"{"input:max_adr": 205, "urn:x:y": 1, "msg": "error: disk full", "time": "12:30", "url": "http://host:80"}"#;
+
+        assert_eq!(prepared_str, expected_str);
+
+        let prepared_str = PrepareOptions::new()
+            .dialect(Dialect::Go)
+            .build()
+            .prepare("{Name:pump Desc:has: colon}");
+
+        assert_eq!(prepared_str, r#"{"Name": "pump", "Desc": "has: colon"}"#);
+    }
+
+    #[test]
+    fn namespaced_keys() {
+        let preparer = PrepareOptions::new().namespaced_keys(true).build();
+
+        let prepared_str = preparer.prepare("{input:min_adr: 7, time:12:30, url: http://host:80}");
+
+        let expected_str = r#"{"input:min_adr": 7, "time": "12:30", "url": "http://host:80"}"#;
+
+        assert_eq!(prepared_str, expected_str);
+        assert_eq!(
+            preparer.prepare("input:max_adr: 205"),
+            r#"{"input:max_adr": 205}"#
+        );
+    }
+
+    #[test]
+    fn stray_closers_are_errors() {
+        let error = try_prepare_json_string("}{").unwrap_err();
+        assert_eq!(error.kind, PrepareErrorKind::UnbalancedBracket);
+        assert_eq!(error.offset, 0);
+
+        let error = try_prepare_json_string("x]").unwrap_err();
+        assert_eq!(error.kind, PrepareErrorKind::UnbalancedBracket);
+        assert_eq!(error.offset, 1);
+    }
+
+    #[test]
+    fn literals_without_json_spelling_become_null() {
+        let original_str = r#"{a: None, b: undefined, c: NaN, d: -Infinity, e: +inf, f: 1e999, g: "NaN", h: None of them}"#;
+
+        let prepared_str = prepare_json_string(original_str);
+
+        let expected_str = r#"{"a": null, "b": null, "c": null, "d": null, "e": null, "f": null, "g": "NaN", "h": "None of them"}"#;
 
         assert_eq!(prepared_str, expected_str);
+        assert!(serde_json::from_str::<serde_json::Value>(&prepared_str).is_ok());
     }
 }
@@ -0,0 +1,284 @@
+use crate::public::preparer::Preparer;
+
+/// What the prepared document looks like at the top level.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TopLevel {
+    /// Keep whatever value the input holds.
+    #[default]
+    AsIs,
+    /// Always produce an array, wrapping the input's elements in one if needed.
+    Array,
+}
+
+/// How scalar leaves are rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ValueMode {
+    /// Numbers, booleans and null stay as they are.
+    #[default]
+    Typed,
+    /// Every leaf becomes a string, so `17` is rendered as `"17"`.
+    Stringified,
+}
+
+/// The language the input was written in. Every dialect also accepts the sloppy JSON that
+/// [`Dialect::Json`] does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// JSON with unquoted keys and values, stray separators and missing brackets.
+    #[default]
+    Json,
+    /// Python `repr`/`str` output: `'single'` and `b'bytes'` strings with Python escapes,
+    /// `True`/`False`/`None`, tuples and sets. `dict(..)`, `OrderedDict(..)` and calls with
+    /// `key=value` arguments are objects, a call with one argument, like `Decimal('1.5')`, is
+    /// that argument, and other calls, like `datetime.date(2024, 1, 2)`, stay strings.
+    Python,
+    /// JavaScript object literals: `'single'` and `` `backtick` `` strings, `0x` numbers and
+    /// strings concatenated with `+`. `undefined` follows the [`InvalidLiteralPolicy`].
+    JavaScript,
+    /// JSON5: comments, `'single'` strings with line continuations, `0x` numbers, `.5`, `5.`
+    /// and `+5`. `Infinity` and `NaN` follow the [`InvalidLiteralPolicy`].
+    Json5,
+    /// Hjson: members on separate lines without commas, quoteless strings running to the end
+    /// of the line, `#`, `//` and `/* */` comments and `'''` multi-line strings.
+    Hjson,
+    /// Ruby `inspect` output: `=>` between keys and values, `:symbol` keys and values, which
+    /// become strings, and `nil`.
+    Ruby,
+    /// Perl `Data::Dumper` output: `=>` between keys and values, `undef`, and a leading
+    /// `$VAR1 =` assignment with its trailing `;`.
+    Perl,
+    /// PHP `var_export`, `print_r` and short array syntax. Arrays whose keys run 0, 1, 2, ...
+    /// become JSON arrays and all others become objects.
+    Php,
+    /// Java, Kotlin and C# `toString()` output: `{a=1, b=[x, y]}` maps and `Name(a=1)`,
+    /// `Name[a=1]` or `Name { A = 1 }` objects, with `:` accepted as well as `=`. A member
+    /// holding both, such as `urn:a=1`, is split at the `=`. See [`PrepareOptions::type_key`]
+    /// for keeping the type name. `toString()` does not quote values, so a value holding a `,`,
+    /// as in `{note=hi, there}`, is cut at the comma: the rest has no key and is dropped with a
+    /// [`crate::PrepareErrorKind::OrphanKey`] error. Values in single quotes, as generated
+    /// `toString()` methods print them in `Person{name='Ann'}`, are strings.
+    Java,
+    /// Go `%v` and `%+v` output: `{ID:5 Tags:[a b]}` structs and `map[a:1 b:2]` maps with
+    /// space-separated members and elements, `&` before pointers, and `<nil>`.
+    Go,
+    /// Rust `{:?}` and `{:#?}` output. Struct names are dropped, or kept under
+    /// [`PrepareOptions::type_key`] when it is set. `Some(x)` is `x`, `None` and `()` are null,
+    /// `Ok(x)` and `Err(x)` become tagged objects such as `{"Ok": x}`, and tuples and `vec![..]`
+    /// are arrays. Other tuple variants and tuple structs, which `Debug` prints alike, lose
+    /// their name, so `Meters(2.5)` is `2.5`, unless [`PrepareOptions::tag_tuple_variants`]
+    /// is set.
+    Rust,
+    /// Swift and Objective-C descriptions: `["name": "x"]` and `[:]` dictionaries,
+    /// `{ name = x; }` `NSDictionary` and `( a, b )` `NSArray` output, `Optional(x)` and
+    /// `AnyHashable(x)`, which are `x`, also as dictionary keys, `nil` and `<null>`.
+    Swift,
+}
+
+/// A comment syntax that is stripped from the input.
+///
+/// Comments only start at the beginning of the input or after whitespace, a bracket or a
+/// separator, and never inside a quoted string, so the `//` in `http://host/a//b` and the `#`
+/// in `/page#top` are kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CommentStyle {
+    /// `// ...` up to the end of the line.
+    DoubleSlash,
+    /// `/* ... */`, which may span lines.
+    SlashStar,
+    /// `# ...` up to the end of the line.
+    Hash,
+}
+
+/// What to do with a bracketed value that sits in an object without a key, as in `{[1, 2]}`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum KeylessPolicy {
+    /// Replace an object whose only member is a keyless value with that value, so `{[1, 2]}`
+    /// becomes `[1, 2]`. Keyless values next to other members are dropped and reported as errors.
+    #[default]
+    Unwrap,
+    /// Put each keyless value under this key. Further keyless values in the same object get
+    /// the key with `_2`, `_3` and so on appended.
+    SyntheticKey(String),
+    /// Drop keyless values and report them as errors.
+    Error,
+}
+
+/// What to do with literals that have no JSON spelling: `None`, `undefined`, `NaN`, `Infinity`
+/// and numbers too large to be finite. Only applies to [`ValueMode::Typed`]; stringified
+/// output keeps them as written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum InvalidLiteralPolicy {
+    /// Replace them with `null`.
+    #[default]
+    Null,
+    /// Keep them as strings, so `NaN` becomes `"NaN"`.
+    String,
+    /// Drop object members that hold them. Elsewhere they become `null`.
+    DropKey,
+}
+
+/// Settings for a [`Preparer`].
+///
+/// ```
+/// use json_string::{PrepareOptions, TopLevel, ValueMode};
+///
+/// let preparer = PrepareOptions::new()
+///     .top_level(TopLevel::Array)
+///     .value_mode(ValueMode::Stringified)
+///     .build();
+///
+/// assert_eq!(preparer.prepare("{a: 17}; {a: 18}"), r#"[{"a": "17"}, {"a": "18"}]"#);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct PrepareOptions {
+    pub(crate) dialect: Dialect,
+    pub(crate) top_level: TopLevel,
+    pub(crate) value_mode: ValueMode,
+    pub(crate) separators: Vec<char>,
+    pub(crate) trim_escaped_newlines: bool,
+    pub(crate) keyless_policy: KeylessPolicy,
+    pub(crate) invalid_literal_policy: InvalidLiteralPolicy,
+    pub(crate) comments: Option<Vec<CommentStyle>>,
+    pub(crate) report_comments: bool,
+    pub(crate) type_key: Option<String>,
+    pub(crate) tag_tuple_variants: bool,
+    pub(crate) namespaced_keys: bool,
+}
+
+impl Default for PrepareOptions {
+    fn default() -> Self {
+        Self {
+            dialect: Dialect::Json,
+            top_level: TopLevel::AsIs,
+            value_mode: ValueMode::Typed,
+            separators: vec![',', ';'],
+            trim_escaped_newlines: true,
+            keyless_policy: KeylessPolicy::Unwrap,
+            invalid_literal_policy: InvalidLiteralPolicy::Null,
+            comments: None,
+            report_comments: false,
+            type_key: None,
+            tag_tuple_variants: false,
+            namespaced_keys: false,
+        }
+    }
+}
+
+impl PrepareOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    #[must_use]
+    pub fn top_level(mut self, top_level: TopLevel) -> Self {
+        self.top_level = top_level;
+        self
+    }
+
+    #[must_use]
+    pub fn value_mode(mut self, value_mode: ValueMode) -> Self {
+        self.value_mode = value_mode;
+        self
+    }
+
+    /// Characters that separate array elements and object members. Defaults to `,` and `;`.
+    /// Only ASCII punctuation can act as a separator; other characters are ignored.
+    #[must_use]
+    pub fn separators(mut self, separators: &[char]) -> Self {
+        self.separators = separators
+            .iter()
+            .copied()
+            .filter(|separator| {
+                separator.is_ascii_punctuation()
+                    && !matches!(separator, '{' | '}' | '[' | ']' | ':' | '"')
+            })
+            .collect();
+        self
+    }
+
+    /// Whether the literal characters `\n` and `\\n` between tokens are treated as
+    /// whitespace, as they are in twice-escaped exports. Defaults to `true`.
+    #[must_use]
+    pub fn trim_escaped_newlines(mut self, trim_escaped_newlines: bool) -> Self {
+        self.trim_escaped_newlines = trim_escaped_newlines;
+        self
+    }
+
+    #[must_use]
+    pub fn keyless_policy(mut self, keyless_policy: KeylessPolicy) -> Self {
+        self.keyless_policy = keyless_policy;
+        self
+    }
+
+    #[must_use]
+    pub fn invalid_literal_policy(mut self, invalid_literal_policy: InvalidLiteralPolicy) -> Self {
+        self.invalid_literal_policy = invalid_literal_policy;
+        self
+    }
+
+    /// Comment styles to strip, replacing those of the dialect. [`Dialect::Json`] and
+    /// [`Dialect::Python`] strip none by default, so `a: 1 // note` keeps `1 // note` as the
+    /// value unless [`CommentStyle::DoubleSlash`] is selected here.
+    #[must_use]
+    pub fn comments(mut self, comment_styles: &[CommentStyle]) -> Self {
+        self.comments = Some(comment_styles.to_vec());
+        self
+    }
+
+    /// Whether every stripped comment is listed, with its text, among the repairs reported
+    /// by [`Preparer::prepare_with_report`]. Defaults to `false`.
+    #[must_use]
+    pub fn report_comments(mut self, report_comments: bool) -> Self {
+        self.report_comments = report_comments;
+        self
+    }
+
+    /// Keeps the type name of objects written as `Name(...)` under this key, such as `"@type"`,
+    /// as the first member of the object. By default the name is dropped.
+    #[must_use]
+    pub fn type_key(mut self, type_key: impl Into<String>) -> Self {
+        self.type_key = Some(type_key.into());
+        self
+    }
+
+    /// Whether [`Dialect::Rust`] tuple variants and tuple structs other than `Ok(..)`, `Err(..)`
+    /// and `Some(..)` become objects keyed by their name, such as `{"Parse": x}` for
+    /// `Parse(x)`. Defaults to `false`, which drops the name as it is for `Meters(2.5)`.
+    #[must_use]
+    pub fn tag_tuple_variants(mut self, tag_tuple_variants: bool) -> Self {
+        self.tag_tuple_variants = tag_tuple_variants;
+        self
+    }
+
+    /// Whether unquoted keys may hold colons, as in `input:max_adr: 205`, where the key runs up
+    /// to the first colon followed by whitespace. Defaults to `false`: unquoted keys end at the
+    /// first colon, so `{msg:error: disk full}` has the key `msg`. Quoted keys may always hold
+    /// colons.
+    #[must_use]
+    pub fn namespaced_keys(mut self, namespaced_keys: bool) -> Self {
+        self.namespaced_keys = namespaced_keys;
+        self
+    }
+
+    pub fn build(self) -> Preparer {
+        Preparer::new(self)
+    }
+
+    pub(crate) fn is_separator(&self, byte: u8) -> bool {
+        self.separators.contains(&char::from(byte))
+    }
+
+    pub(crate) fn comment_styles(&self) -> &[CommentStyle] {
+        match &self.comments {
+            Some(comment_styles) => comment_styles,
+            None => self.dialect.syntax().comments,
+        }
+    }
+}
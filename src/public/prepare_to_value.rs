@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+use crate::public::{
+    prepare_error::PrepareError,
+    prepare_options::{PrepareOptions, TopLevel},
+};
+
+pub fn prepare_to_value(original_str: &str) -> Result<Value, PrepareError> {
+    let json_value = PrepareOptions::new()
+        .build()
+        .prepare_to_value(original_str)?;
+
+    Ok(json_value)
+}
+
+pub fn prepare_array_to_value(original_str: &str) -> Result<Value, PrepareError> {
+    let json_value = PrepareOptions::new()
+        .top_level(TopLevel::Array)
+        .build()
+        .prepare_to_value(original_str)?;
+
+    Ok(json_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{prepare_array_to_value, prepare_json_string, prepare_to_value};
+
+    #[test]
+    fn matches_parsing_the_prepared_string() {
+        let original_str = r#"{
+            "units": [{"unit_id": 5,"ec_id": 0,"label": "SOMETHING", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"] }]
+        }"#;
+
+        let json_value = prepare_to_value(original_str).unwrap();
+
+        let expected_value =
+            serde_json::from_str::<serde_json::Value>(&prepare_json_string(original_str)).unwrap();
+
+        assert_eq!(json_value, expected_value);
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn key_order_follows_the_input() {
+        let original_str = "{zulu: 1, alpha: 2, mike: {yankee: true, bravo: null}}";
+
+        let json_value = prepare_to_value(original_str).unwrap();
+
+        let serialized = serde_json::to_string(&json_value).unwrap();
+
+        let expected_str = r#"{"zulu":1,"alpha":2,"mike":{"yankee":true,"bravo":null}}"#;
+
+        assert_eq!(serialized, expected_str);
+    }
+
+    #[test]
+    fn prepare_array_to_value_wraps_objects() {
+        let original_str = "{a: 1.5}; {a: -2};";
+
+        let json_value = prepare_array_to_value(original_str).unwrap();
+
+        assert_eq!(json_value, json!([{"a": 1.5}, {"a": -2}]));
+    }
+
+    #[test]
+    fn values_without_json_representation_become_null() {
+        let json_value = prepare_to_value("{a: inf, b: None, c: [NaN, undefined, 1e999]}").unwrap();
+
+        assert_eq!(
+            json_value,
+            json!({"a": null, "b": null, "c": [null, null, null]})
+        );
+    }
+
+    #[test]
+    fn escaped_quotes_are_decoded_once() {
+        let original_str = r#"{label: "He said \"hi\", then left", path: "C:\\temp\\"}"#;
+
+        let json_value = prepare_to_value(original_str).unwrap();
+
+        assert_eq!(
+            json_value,
+            json!({"label": "He said \"hi\", then left", "path": "C:\\temp\\"})
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::public::{prepare_options::PrepareOptions, repair::Repair};
+
+/// Prepares the input like [`crate::prepare_json_string`] and lists every change made to it,
+/// ordered by where in the input it was made.
+pub fn prepare_with_report(original_str: &str) -> (String, Vec<Repair>) {
+    let prepared_with_report = PrepareOptions::new()
+        .build()
+        .prepare_with_report(original_str);
+
+    prepared_with_report
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prepare_json_string, prepare_with_report, RepairKind, Span};
+
+    #[test]
+    fn output_matches_prepare_json_string() {
+        let original_str =
+            r#"{\n\n"Description": \n"Battery pack interfaces 1, NB011-NB012 (UPS 1)", \n}"#;
+
+        let (prepared_str, _) = prepare_with_report(original_str);
+
+        assert_eq!(prepared_str, prepare_json_string(original_str));
+    }
+
+    #[test]
+    fn valid_json_needs_no_repairs() {
+        let original_str = r#"{"tag": "lol", "groups": [1, true, null]}"#;
+
+        let (_, repairs) = prepare_with_report(original_str);
+
+        assert_eq!(repairs, Vec::new());
+    }
+
+    #[test]
+    fn every_repair_is_reported() {
+        let original_str = "{a: lol; b: TRUE, lonely,, c: [1, 2}";
+
+        let (prepared_str, repairs) = prepare_with_report(original_str);
+
+        assert_eq!(prepared_str, r#"{"a": "lol", "b": true, "c": [1, 2]}"#);
+
+        let kinds_and_spans = repairs
+            .iter()
+            .map(|repair| (repair.kind, repair.span))
+            .collect::<Vec<_>>();
+
+        let expected_kinds_and_spans = vec![
+            (RepairKind::QuotedKey, Span::new(1, 2)),
+            (RepairKind::QuotedValue, Span::new(4, 7)),
+            (RepairKind::ReplacedSeparator, Span::new(7, 8)),
+            (RepairKind::QuotedKey, Span::new(9, 10)),
+            (RepairKind::NormalizedLiteral, Span::new(12, 16)),
+            (RepairKind::DroppedMember, Span::new(18, 24)),
+            (RepairKind::DroppedSeparator, Span::new(25, 26)),
+            (RepairKind::QuotedKey, Span::new(27, 28)),
+            (RepairKind::ClosedBracket, Span::new(35, 35)),
+        ];
+
+        assert_eq!(kinds_and_spans, expected_kinds_and_spans);
+        assert_eq!(repairs[4].message, "rewrote `TRUE` as `true`");
+        assert_eq!(repairs[5].message, "dropped `lonely`, which has no key");
+    }
+}
@@ -0,0 +1,483 @@
+use std::borrow::Cow;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    helpers::{
+        ensure_array_wrapper, is_strict_json, render_value, to_json_value, value_repairs,
+        JsonContext,
+    },
+    parser::{parse_outcome, ParseOutcome},
+    public::{
+        deserialize_error::DeserializeError,
+        lenient_deserializer::LenientDeserializer,
+        lenient_value::LenientValue,
+        prepare_error::PrepareError,
+        prepare_fragment::FragmentKind,
+        prepare_options::{PrepareOptions, TopLevel, ValueMode},
+        repair::Repair,
+    },
+};
+
+/// Prepares inputs according to one set of [`PrepareOptions`]. Build it once and reuse it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Preparer {
+    options: PrepareOptions,
+}
+
+impl Preparer {
+    pub fn new(options: PrepareOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn options(&self) -> &PrepareOptions {
+        &self.options
+    }
+
+    pub fn prepare(&self, original_str: &str) -> String {
+        let parse_outcome = self.parse(original_str);
+
+        let prepared_string = render_value(&parse_outcome.lenient_value, self.options.value_mode);
+
+        prepared_string
+    }
+
+    /// Returns the input unchanged when it is already valid JSON of the requested shape, and
+    /// repairs it otherwise. Stringified preparers always return an owned string.
+    pub fn prepare_cow<'a>(&self, original_str: &'a str) -> Cow<'a, str> {
+        let has_requested_shape = match self.options.top_level {
+            TopLevel::AsIs => true,
+            TopLevel::Array => original_str.trim_start().starts_with('['),
+        };
+
+        if self.options.value_mode == ValueMode::Typed
+            && has_requested_shape
+            && is_strict_json(original_str)
+        {
+            return Cow::Borrowed(original_str);
+        }
+
+        Cow::Owned(self.prepare(original_str))
+    }
+
+    pub fn try_prepare(&self, original_str: &str) -> Result<String, PrepareError> {
+        let parse_outcome = self.try_parse(original_str)?;
+
+        let prepared_string = render_value(&parse_outcome.lenient_value, self.options.value_mode);
+
+        Ok(prepared_string)
+    }
+
+    /// Object keys keep their input order only with the default `preserve_order` feature, which
+    /// turns on `serde_json/preserve_order` for the whole dependency graph.
+    pub fn prepare_to_value(&self, original_str: &str) -> Result<Value, PrepareError> {
+        let parse_outcome = self.try_parse(original_str)?;
+
+        let json_value = to_json_value(
+            &parse_outcome.lenient_value,
+            self.options.value_mode,
+            original_str,
+        )?;
+
+        Ok(json_value)
+    }
+
+    /// Parses the input into a [`LenientValue`] tree without rendering it. See
+    /// [`crate::parse_lenient_json_string`].
+    pub fn parse_lenient(&self, original_str: &str) -> LenientValue {
+        let parse_outcome = self.parse(original_str);
+
+        parse_outcome.lenient_value
+    }
+
+    /// Deserializes the input into `T`, coercing scalars to the types it asks for. See
+    /// [`crate::from_str`].
+    pub fn from_str<T: DeserializeOwned>(&self, original_str: &str) -> Result<T, DeserializeError> {
+        let parse_outcome = self.try_parse(original_str)?;
+
+        let deserialized = T::deserialize(LenientDeserializer::new(
+            &parse_outcome.lenient_value,
+            original_str,
+        ))?;
+
+        Ok(deserialized)
+    }
+
+    /// Prepares a fragment of the given kind, ignoring the top-level setting. See
+    /// [`crate::prepare_fragment`].
+    pub fn prepare_fragment(&self, original_str: &str, fragment_kind: FragmentKind) -> String {
+        let parse_outcome =
+            parse_outcome(original_str, fragment_kind.json_context(), &self.options);
+
+        let prepared_string = render_value(&parse_outcome.lenient_value, self.options.value_mode);
+
+        prepared_string
+    }
+
+    pub fn try_prepare_fragment(
+        &self,
+        original_str: &str,
+        fragment_kind: FragmentKind,
+    ) -> Result<String, PrepareError> {
+        let parse_outcome = first_error(parse_outcome(
+            original_str,
+            fragment_kind.json_context(),
+            &self.options,
+        ))?;
+
+        let prepared_string = render_value(&parse_outcome.lenient_value, self.options.value_mode);
+
+        Ok(prepared_string)
+    }
+
+    /// Prepares the input and lists every change made to it, ordered by where in the input
+    /// it was made.
+    pub fn prepare_with_report(&self, original_str: &str) -> (String, Vec<Repair>) {
+        let parse_outcome = self.parse(original_str);
+        let lenient_value = parse_outcome.lenient_value;
+
+        let mut repairs = parse_outcome.repairs;
+        value_repairs(
+            &lenient_value,
+            self.options.value_mode,
+            original_str,
+            &mut repairs,
+        );
+        repairs.sort_by_key(|repair| repair.span.start);
+
+        let prepared_string = render_value(&lenient_value, self.options.value_mode);
+
+        (prepared_string, repairs)
+    }
+
+    fn parse(&self, original_str: &str) -> ParseOutcome {
+        let parse_outcome = match self.options.top_level {
+            TopLevel::AsIs => parse_outcome(original_str, JsonContext::Value, &self.options),
+            TopLevel::Array => {
+                let mut parse_outcome =
+                    parse_outcome(original_str, JsonContext::Array, &self.options);
+                parse_outcome.lenient_value = ensure_array_wrapper(parse_outcome.lenient_value);
+
+                parse_outcome
+            }
+        };
+
+        parse_outcome
+    }
+
+    fn try_parse(&self, original_str: &str) -> Result<ParseOutcome, PrepareError> {
+        first_error(self.parse(original_str))
+    }
+}
+
+fn first_error(mut parse_outcome: ParseOutcome) -> Result<ParseOutcome, PrepareError> {
+    if parse_outcome.errors.is_empty() {
+        Ok(parse_outcome)
+    } else {
+        Err(parse_outcome.errors.swap_remove(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        CommentStyle, Dialect, InvalidLiteralPolicy, KeylessPolicy, PrepareErrorKind,
+        PrepareOptions, RepairKind, TopLevel, ValueMode,
+    };
+
+    #[test]
+    fn comma_inside_value() {
+        let original_str = r#"{"Description": "Battery pack interfaces 1, NB011-NB012 (UPS 1)", }"#;
+
+        let expected_str =
+            r#"{"Description": "Battery pack interfaces 1, NB011-NB012 (UPS 1)"}"#.to_string();
+
+        for value_mode in [ValueMode::Typed, ValueMode::Stringified] {
+            let preparer = PrepareOptions::new().value_mode(value_mode).build();
+            let prepared_str = preparer.prepare(original_str);
+
+            assert_eq!(prepared_str, expected_str);
+        }
+    }
+
+    #[test]
+    fn array_w_multiple_objects_stringified1() {
+        let original_str = r#"[
+            {"Foo1":"BAR1", "Foo2":"BAR2", "Foo3":"BAR3"};
+            {"Foo4":"BAR4", "Foo5":"BAR5", "Foo6":"BAR6"};
+            {"Foo7":"BAR7", "Foo8":"BAR8", "Foo9":"BAR9"}
+        ]"#;
+
+        let preparer = PrepareOptions::new()
+            .value_mode(ValueMode::Stringified)
+            .build();
+        let prepared_str = preparer.prepare(original_str);
+
+        let expected_str = r#"[{"Foo1": "BAR1", "Foo2": "BAR2", "Foo3": "BAR3"}, {"Foo4": "BAR4", "Foo5": "BAR5", "Foo6": "BAR6"}, {"Foo7": "BAR7", "Foo8": "BAR8", "Foo9": "BAR9"}]"#
+        .to_string();
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn array_w_multiple_objects_stringified2() {
+        let original_str = r#"[
+            {"Foo1":"BAR1", "Foo2":"BAR2", "Foo3":"BAR3"},
+            {"Foo4":"BAR4", "Foo5":"BAR5", "Foo6":"BAR6"};
+            {"Foo7":"BAR7", "Foo8":"BAR8", "Foo9":"BAR9"}
+        ]"#;
+
+        let preparer = PrepareOptions::new()
+            .value_mode(ValueMode::Stringified)
+            .build();
+        let prepared_str = preparer.prepare(original_str);
+
+        let expected_str = r#"[{"Foo1": "BAR1", "Foo2": "BAR2", "Foo3": "BAR3"}, {"Foo4": "BAR4", "Foo5": "BAR5", "Foo6": "BAR6"}, {"Foo7": "BAR7", "Foo8": "BAR8", "Foo9": "BAR9"}]"#
+        .to_string();
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn parse_json_string_test() {
+        let original_str = r#"{
+            [{unit_id: 5, ec_id: 0,"label": "SOMETHING","uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"] }],
+        }"#;
+
+        let prepared_str = PrepareOptions::new().build().prepare(original_str);
+
+        let expected_str = r#"[{"unit_id": 5, "ec_id": 0, "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]"#.to_string();
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn parse_stringified_json_string_test() {
+        let original_str = r#"{
+            [{unit_id: 5, ec_id: 0,"label": "SOMETHING","uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"] }],
+        }"#;
+
+        let preparer = PrepareOptions::new()
+            .value_mode(ValueMode::Stringified)
+            .build();
+        let prepared_str = preparer.prepare(original_str);
+
+        let expected_str = r#"[{"unit_id": "5", "ec_id": "0", "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]"#.to_string();
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn custom_separators() {
+        let original_str = "{a: 1 | b: x;y}";
+
+        let preparer = PrepareOptions::new().separators(&['|']).build();
+        let (prepared_str, repairs) = preparer.prepare_with_report(original_str);
+
+        assert_eq!(prepared_str, r#"{"a": 1, "b": "x;y"}"#);
+        assert!(repairs
+            .iter()
+            .any(|repair| repair.kind == RepairKind::ReplacedSeparator));
+    }
+
+    #[test]
+    fn escaped_newlines_can_be_kept() {
+        let original_str = r"[\na, b\n]";
+
+        let preparer = PrepareOptions::new()
+            .top_level(TopLevel::Array)
+            .trim_escaped_newlines(false)
+            .build();
+        let json_value = preparer.prepare_to_value(original_str).unwrap();
+
+        assert_eq!(json_value, json!([r"\na", r"b\n"]));
+    }
+
+    #[test]
+    fn reused_preparer_wraps_every_input() {
+        let preparer = PrepareOptions::new().top_level(TopLevel::Array).build();
+
+        assert_eq!(preparer.prepare("{a: 1}"), r#"[{"a": 1}]"#);
+        assert_eq!(preparer.prepare("[1, 2]"), "[1, 2]");
+        assert_eq!(preparer.try_prepare("x; y").unwrap(), r#"["x", "y"]"#);
+    }
+
+    #[test]
+    fn keyless_policies() {
+        let original_str = "{a: 1, [x], {b: 2}}";
+
+        let preparer = PrepareOptions::new()
+            .keyless_policy(KeylessPolicy::SyntheticKey("value".to_string()))
+            .build();
+        let json_value = preparer.prepare_to_value(original_str).unwrap();
+        assert_eq!(
+            json_value,
+            json!({"a": 1, "value": ["x"], "value_2": {"b": 2}})
+        );
+
+        let preparer = PrepareOptions::new()
+            .keyless_policy(KeylessPolicy::Error)
+            .build();
+        assert_eq!(preparer.prepare("{[1, 2]}"), "{}");
+        let error = preparer.try_prepare("{[1, 2]}").unwrap_err();
+        assert_eq!(error.kind, PrepareErrorKind::KeylessValue);
+
+        let preparer = PrepareOptions::new().build();
+        let (prepared_str, repairs) = preparer.prepare_with_report("{{b: 2}}");
+        assert_eq!(prepared_str, r#"{"b": 2}"#);
+        assert_eq!(repairs[0].kind, RepairKind::UnwrappedKeylessValue);
+    }
+
+    #[test]
+    fn keyless_values_always_render_valid_json() {
+        let original_strs = [
+            "{[1, 2]}",
+            "{{[a]}}",
+            "{a: 1, [x]}",
+            "{[1], [2]}",
+            "[{[1]}, {b}]",
+        ];
+        let keyless_policies = [
+            KeylessPolicy::Unwrap,
+            KeylessPolicy::SyntheticKey("value".to_string()),
+            KeylessPolicy::Error,
+        ];
+
+        for keyless_policy in keyless_policies {
+            let preparer = PrepareOptions::new().keyless_policy(keyless_policy).build();
+
+            for original_str in original_strs {
+                let prepared_str = preparer.prepare(original_str);
+
+                assert!(
+                    serde_json::from_str::<serde_json::Value>(&prepared_str).is_ok(),
+                    "{original_str} became {prepared_str}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_literal_policies() {
+        let original_str = "{a: 1, b: NaN, c: [None, 2], d: Infinity}";
+
+        let preparer = PrepareOptions::new()
+            .invalid_literal_policy(InvalidLiteralPolicy::String)
+            .build();
+        assert_eq!(
+            preparer.prepare(original_str),
+            r#"{"a": 1, "b": "NaN", "c": ["None", 2], "d": "Infinity"}"#
+        );
+
+        let preparer = PrepareOptions::new()
+            .invalid_literal_policy(InvalidLiteralPolicy::DropKey)
+            .build();
+        let (prepared_str, repairs) = preparer.prepare_with_report(original_str);
+        assert_eq!(prepared_str, r#"{"a": 1, "c": [null, 2]}"#);
+        assert_eq!(repairs[2].kind, RepairKind::DroppedMember);
+        assert_eq!(
+            repairs[2].message,
+            "dropped `b`, whose value `NaN` has no JSON equivalent"
+        );
+
+        let preparer = PrepareOptions::new()
+            .value_mode(ValueMode::Stringified)
+            .build();
+        assert_eq!(
+            preparer.prepare(original_str),
+            r#"{"a": "1", "b": "NaN", "c": ["None", "2"], "d": "Infinity"}"#
+        );
+    }
+
+    #[test]
+    fn comment_styles_are_selectable() {
+        let original_str = "{
+            a: 1, // note, with a comma
+            url: http://host/a//b#top,
+            b: /* x, y */ 2, # c: 3
+            d: \"// kept\"
+        }";
+
+        let preparer = PrepareOptions::new()
+            .comments(&[
+                CommentStyle::DoubleSlash,
+                CommentStyle::SlashStar,
+                CommentStyle::Hash,
+            ])
+            .build();
+        assert_eq!(
+            preparer.prepare(original_str),
+            r#"{"a": 1, "url": "http://host/a//b#top", "b": 2, "d": "// kept"}"#
+        );
+
+        let preparer = PrepareOptions::new()
+            .comments(&[CommentStyle::Hash])
+            .build();
+        assert_eq!(
+            preparer.prepare("{a: x // y, b: 1 # z}"),
+            r#"{"a": "x // y", "b": 1}"#
+        );
+
+        let preparer = PrepareOptions::new()
+            .dialect(Dialect::Json5)
+            .comments(&[])
+            .build();
+        assert_eq!(preparer.prepare("[a // b]"), r#"["a // b"]"#);
+    }
+
+    #[test]
+    fn comments_can_be_reported() {
+        let original_str = "{a: 1 /* one */, b: hello /* inner */ world} // done";
+
+        let preparer = PrepareOptions::new()
+            .comments(&[CommentStyle::DoubleSlash, CommentStyle::SlashStar])
+            .report_comments(true)
+            .build();
+        let (prepared_str, repairs) = preparer.prepare_with_report(original_str);
+
+        assert_eq!(prepared_str, r#"{"a": 1, "b": "hello world"}"#);
+        let comment_messages = repairs
+            .iter()
+            .filter(|repair| repair.kind == RepairKind::DroppedComment)
+            .map(|repair| repair.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            comment_messages,
+            [
+                "dropped comment `/* one */`",
+                "dropped comment `/* inner */`",
+                "dropped comment `// done`"
+            ]
+        );
+
+        let preparer = PrepareOptions::new()
+            .comments(&[CommentStyle::DoubleSlash, CommentStyle::SlashStar])
+            .build();
+        let (_, repairs) = preparer.prepare_with_report(original_str);
+        assert!(repairs
+            .iter()
+            .all(|repair| repair.kind != RepairKind::DroppedComment));
+    }
+
+    #[test]
+    fn dialect_aware_deserializing_and_parsing() {
+        let preparer = PrepareOptions::new().dialect(Dialect::Python).build();
+
+        let pairs = preparer
+            .from_str::<Vec<(String, Option<bool>)>>("[('a', True), ('b', None)]")
+            .unwrap();
+        assert_eq!(
+            pairs,
+            [("a".to_string(), Some(true)), ("b".to_string(), None)]
+        );
+
+        let lenient_value = preparer.parse_lenient("{'a': (1, 2)}");
+        assert_eq!(lenient_value.get("a").unwrap().as_array().unwrap().len(), 2);
+
+        let error = preparer.from_str::<Vec<u8>>("[1, 2").unwrap_err();
+        assert_eq!(error.kind, Some(PrepareErrorKind::UnbalancedBracket));
+    }
+}
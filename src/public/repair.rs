@@ -0,0 +1,68 @@
+use std::fmt;
+
+use crate::public::lenient_value::Span;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RepairKind {
+    /// An unquoted key was wrapped in quotes.
+    QuotedKey,
+    /// Unquoted text was turned into a string.
+    QuotedValue,
+    /// A literal or number was rewritten in its JSON spelling, such as `TRUE` to `true`.
+    NormalizedLiteral,
+    /// A number, boolean or null was turned into a string by a stringified preset.
+    StringifiedValue,
+    /// A `;` separator was replaced with `,`.
+    ReplacedSeparator,
+    /// A leading, repeated or trailing separator was dropped.
+    DroppedSeparator,
+    /// A literal `\n` between tokens was dropped.
+    DroppedEscapedNewline,
+    /// A comment was dropped. Only reported when [`crate::PrepareOptions::report_comments`]
+    /// is set.
+    DroppedComment,
+    /// An object member without a usable key was dropped.
+    DroppedMember,
+    /// The braces around an object's only, keyless, value were removed.
+    UnwrappedKeylessValue,
+    /// A keyless value was given a synthetic key.
+    AddedKey,
+    /// A key without a value was given `null`.
+    FilledMissingValue,
+    /// A missing closing bracket or brace was inserted.
+    ClosedBracket,
+    /// A closing bracket or brace without an opener was dropped.
+    DroppedBracket,
+    /// A closing quote was added to a string that ran to the end of the input.
+    ClosedString,
+    /// Content after a complete value was dropped.
+    DroppedContent,
+}
+
+/// One change made to the input while preparing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Repair {
+    pub kind: RepairKind,
+    pub span: Span,
+    pub message: String,
+}
+
+impl Repair {
+    pub(crate) fn new(kind: RepairKind, span: Span, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (bytes {}..{})",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
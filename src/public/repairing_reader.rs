@@ -0,0 +1,764 @@
+use std::{
+    io::{self, Read},
+    str::Utf8Error,
+};
+
+use crate::{
+    helpers::{ensure_array_wrapper, line_column, render_value, JsonContext},
+    lexer::{split_array_body, tokenize, ArrayBody, TokenKind},
+    parser::parse,
+    public::{
+        deserialize_error::Position,
+        lenient_value::{LenientKind, LenientValue},
+        prepare_error::{PrepareError, PrepareErrorKind},
+        prepare_options::PrepareOptions,
+    },
+};
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Wraps a reader over a sloppy array and yields the same JSON that [`crate::prepare_json_array`]
+/// would produce, one element at a time.
+///
+/// Only the elements not yet repaired are held in memory, so inputs of any size can be fed
+/// to `serde_json::from_reader` or `serde_json::StreamDeserializer`. If the input starts with
+/// `[`, that bracket is taken as the outer array; otherwise the whole input is read as a
+/// sequence of elements. Elements are cut apart with the same tokens [`crate::Preparer`] sees.
+///
+/// Unlike [`crate::prepare_json_array`], which reads `[1, 2] tail, 3` as the two elements
+/// `[1, 2]` and `3`, the reader cannot wait for the end of the input to decide what the outer
+/// array is. Content after the closing `]` of the outer array is therefore an error of kind
+/// [`io::ErrorKind::InvalidData`] wrapping a [`PrepareError`] of kind
+/// [`PrepareErrorKind::UnexpectedContent`]. Whitespace and comments after it are fine.
+///
+/// In [`crate::Dialect::Php`] and [`crate::Dialect::Swift`], keys decide whether brackets hold
+/// an array or an object. Outer brackets whose first member has no key are streamed as an
+/// array, and a key on a later member is an [`PrepareErrorKind::UnexpectedContent`] error.
+/// Outer brackets whose first member has a key hold a single object, which is read whole.
+///
+/// Once the input is found to be invalid, every later read returns the same error.
+pub struct RepairingReader<R> {
+    inner: R,
+    options: PrepareOptions,
+    /// Whether keys decide between an array and an object, as they do for PHP arrays and Swift
+    /// `[...]` literals.
+    keys_decide_shape: bool,
+    read_buffer: Vec<u8>,
+    /// Input read but not yet repaired. Once the outer array is found, this is what follows
+    /// its `[` or the separator after the last repaired element.
+    pending: Vec<u8>,
+    /// Where the first pending byte is in the input.
+    pending_position: Position,
+    /// How long `pending` has to grow before it is lexed again, which keeps lexing linear when
+    /// an element spans many reads.
+    next_lex_len: usize,
+    outer: Outer,
+    output: Vec<u8>,
+    output_position: usize,
+    wrote_element: bool,
+    /// The first element of a bare list, held back until another follows. If none does, the
+    /// input is that one value, and an array is written as the outer array, as
+    /// [`crate::prepare_json_array`] does.
+    first_bare_element: Option<LenientValue>,
+    finished: bool,
+    failure: Option<InvalidInput>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outer {
+    /// Too little has been read to tell whether the input starts with `[`.
+    Undecided,
+    /// The input is a sequence of elements without outer brackets.
+    Bare,
+    /// Outer brackets whose first member may still turn out to have a key.
+    Brackets,
+    /// An outer array whose elements are repaired as they are read.
+    Streamed,
+    /// Outer brackets holding a single object, repaired once the input ends.
+    Whole,
+    /// The outer array is closed, and what follows it is pending.
+    Closed,
+}
+
+/// Why the input could not be repaired. It is kept so that later reads fail the same way.
+#[derive(Clone, Debug)]
+enum InvalidInput {
+    Utf8(Utf8Error),
+    Prepare(PrepareError),
+}
+
+impl InvalidInput {
+    fn to_io_error(&self) -> io::Error {
+        match self {
+            InvalidInput::Utf8(error) => io::Error::new(io::ErrorKind::InvalidData, *error),
+            InvalidInput::Prepare(error) => {
+                io::Error::new(io::ErrorKind::InvalidData, error.clone())
+            }
+        }
+    }
+}
+
+impl<R: Read> RepairingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_options(inner, PrepareOptions::default())
+    }
+
+    /// Repairs elements with the given value mode, separators and trimming rules. The output
+    /// is always an array, so the top-level setting is ignored.
+    pub fn with_options(inner: R, options: PrepareOptions) -> Self {
+        let syntax = options.dialect.syntax();
+
+        Self {
+            inner,
+            keys_decide_shape: syntax.php_arrays || syntax.dictionaries,
+            options,
+            read_buffer: vec![0; READ_BUFFER_SIZE],
+            pending: Vec::new(),
+            pending_position: Position {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            next_lex_len: 0,
+            outer: Outer::Undecided,
+            output: b"[".to_vec(),
+            output_position: 0,
+            wrote_element: false,
+            first_bare_element: None,
+            finished: false,
+            failure: None,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn fill_output(&mut self) -> io::Result<()> {
+        let read_len = match self.inner.read(&mut self.read_buffer) {
+            Ok(read_len) => read_len,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        let at_end = read_len == 0;
+        self.pending
+            .extend_from_slice(&self.read_buffer[..read_len]);
+
+        if at_end || self.pending.len() >= self.next_lex_len {
+            if let Err(invalid_input) = self.repair_pending(at_end) {
+                let error = invalid_input.to_io_error();
+                self.failure = Some(invalid_input);
+                return Err(error);
+            }
+            self.next_lex_len = 2 * self.pending.len();
+        }
+
+        if at_end {
+            if let Some(first_element) = self.first_bare_element.take() {
+                match first_element.kind {
+                    LenientKind::Array(elements) => {
+                        for element in &elements {
+                            self.write_element(element);
+                        }
+                    }
+                    _ => self.write_element(&first_element),
+                }
+            }
+            self.output.push(b']');
+            self.finished = true;
+        }
+
+        Ok(())
+    }
+
+    /// Repairs the pending elements known to be complete, which at the end of the input are all
+    /// of them, and drops them from `pending`.
+    fn repair_pending(&mut self, at_end: bool) -> Result<(), InvalidInput> {
+        let pending = std::mem::take(&mut self.pending);
+        // A character may be cut in two by the end of a read.
+        let pending_str = match std::str::from_utf8(&pending) {
+            Err(error) if error.error_len().is_none() && !at_end => {
+                std::str::from_utf8(&pending[..error.valid_up_to()])
+            }
+            pending_str => pending_str,
+        }
+        .map_err(InvalidInput::Utf8)?;
+
+        // Each step may move on to the next part of the input, as from the `[` to the elements
+        // after it, which is then repaired from where the step stopped.
+        let mut rest = pending_str;
+        loop {
+            let outer = self.outer;
+            let repaired_len = self.repair_str(rest, at_end)?;
+            self.pending_position = self.input_position(rest, repaired_len);
+            rest = &rest[repaired_len..];
+
+            if self.outer == outer {
+                break;
+            }
+        }
+        self.pending = pending[pending_str.len() - rest.len()..].to_vec();
+
+        Ok(())
+    }
+
+    /// Repairs what it can of `pending` and returns how many of its bytes are done with.
+    fn repair_str(&mut self, pending: &str, at_end: bool) -> Result<usize, InvalidInput> {
+        match self.outer {
+            Outer::Undecided => Ok(self.decide_outer(pending, at_end)),
+            Outer::Bare | Outer::Streamed => {
+                let array_body =
+                    split_array_body(pending, self.outer == Outer::Streamed, &self.options);
+                self.repair_members(pending, &array_body, at_end)
+            }
+            Outer::Brackets => {
+                let array_body = split_array_body(pending, true, &self.options);
+                let first_member = array_body.members[0];
+                let is_first_complete =
+                    array_body.members.len() > 2 || array_body.closer.is_some() || at_end;
+
+                if first_member.colon.is_some() {
+                    self.outer = Outer::Whole;
+                } else if is_first_complete {
+                    self.outer = Outer::Streamed;
+                }
+                Ok(0)
+            }
+            Outer::Whole if at_end => {
+                let lenient_value =
+                    parse(&format!("[{pending}"), JsonContext::Array, &self.options);
+                self.write_elements(ensure_array_wrapper(lenient_value));
+                Ok(pending.len())
+            }
+            Outer::Closed if at_end => {
+                self.check_tail(pending)?;
+                Ok(pending.len())
+            }
+            Outer::Whole | Outer::Closed => Ok(0),
+        }
+    }
+
+    /// Looks at the leading tokens to tell a bracketed array from a bare list of elements,
+    /// skipping escaped newlines and comments. Returns how many bytes are done with: up to and
+    /// including the `[` of an outer array, and none otherwise.
+    fn decide_outer(&mut self, pending: &str, at_end: bool) -> usize {
+        let tokens = tokenize(pending, &self.options);
+        let first_index = tokens.iter().position(|token| {
+            let is_blank_separator =
+                token.is_separator() && pending[token.span.start..token.span.end].trim().is_empty();
+            !matches!(token.kind, TokenKind::EscapedNewline | TokenKind::Comment)
+                && !is_blank_separator
+        });
+
+        // A token at the end of what was read may still grow into a comment, as `/` does.
+        let repaired_len = match first_index {
+            Some(index) if tokens[index].kind == TokenKind::LeftBracket => {
+                self.outer = if self.keys_decide_shape {
+                    Outer::Brackets
+                } else {
+                    Outer::Streamed
+                };
+                tokens[index].span.end
+            }
+            Some(index) if index + 1 < tokens.len() || at_end => {
+                self.outer = Outer::Bare;
+                0
+            }
+            None if at_end => {
+                self.outer = Outer::Bare;
+                0
+            }
+            _ => 0,
+        };
+
+        repaired_len
+    }
+
+    /// Repairs the members of an outer array, or of a bare list, that are complete. A separator
+    /// may still turn out to be part of something longer until another separator or the closer
+    /// follows it, so the last one read is only cut at once the input ends.
+    fn repair_members(
+        &mut self,
+        pending: &str,
+        array_body: &ArrayBody,
+        at_end: bool,
+    ) -> Result<usize, InvalidInput> {
+        let members = &array_body.members;
+        // What follows a `]` can still make it part of a print_r key such as `[a] =>`.
+        let closer = array_body
+            .closer
+            .filter(|closer| at_end || closer.end < pending.trim_end().len());
+        let (complete_len, repaired_len) = match closer {
+            Some(closer) => (members.len(), closer.end),
+            None if at_end => (members.len(), pending.len()),
+            None if members.len() > 2 => {
+                let complete_len = members.len() - 2;
+                let separator = members[complete_len - 1].separator;
+                (complete_len, separator.map_or(0, |separator| separator.end))
+            }
+            None => return Ok(0),
+        };
+
+        if self.outer == Outer::Streamed && self.keys_decide_shape {
+            let keyed_member = members[..complete_len]
+                .iter()
+                .find_map(|member| member.colon);
+            if let Some(colon) = keyed_member {
+                return Err(self.prepare_error(pending, colon.start));
+            }
+        }
+
+        self.repair_chunk(&pending[..repaired_len]);
+        if closer.is_some() {
+            self.outer = Outer::Closed;
+        }
+
+        Ok(repaired_len)
+    }
+
+    /// Repairs a chunk of whole elements with the separator or closer after them. Inside the outer
+    /// array it is parsed after a `[`, so it is read in the context it has in the whole input.
+    fn repair_chunk(&mut self, chunk: &str) {
+        let lenient_value = match self.outer {
+            Outer::Bare => parse(chunk, JsonContext::Array, &self.options),
+            _ => parse(&format!("[{chunk}"), JsonContext::Value, &self.options),
+        };
+
+        self.write_elements(lenient_value);
+    }
+
+    fn write_elements(&mut self, lenient_value: LenientValue) {
+        let elements = match lenient_value.kind {
+            LenientKind::Array(elements) => elements,
+            _ => vec![lenient_value],
+        };
+
+        for element in elements {
+            if self.outer == Outer::Bare && !self.wrote_element {
+                let Some(first_element) = self.first_bare_element.take() else {
+                    self.first_bare_element = Some(element);
+                    continue;
+                };
+                self.write_element(&first_element);
+            }
+            self.write_element(&element);
+        }
+    }
+
+    fn write_element(&mut self, element: &LenientValue) {
+        if self.wrote_element {
+            self.output.extend_from_slice(b", ");
+        }
+        let rendered_element = render_value(element, self.options.value_mode);
+        self.output.extend_from_slice(rendered_element.as_bytes());
+        self.wrote_element = true;
+    }
+
+    /// Fails if anything but whitespace and comments follows the outer array.
+    fn check_tail(&self, tail: &str) -> Result<(), InvalidInput> {
+        let lenient_value = parse(tail, JsonContext::Value, &self.options);
+        if lenient_value.kind == LenientKind::Empty {
+            return Ok(());
+        }
+
+        Err(self.prepare_error(tail, lenient_value.span.start))
+    }
+
+    /// An [`PrepareErrorKind::UnexpectedContent`] error at `offset` into the pending input.
+    fn prepare_error(&self, pending: &str, offset: usize) -> InvalidInput {
+        let position = self.input_position(pending, offset);
+
+        InvalidInput::Prepare(PrepareError {
+            kind: PrepareErrorKind::UnexpectedContent,
+            offset: position.offset,
+            line: position.line,
+            column: position.column,
+        })
+    }
+
+    /// Where `offset` into the pending input is in the whole input.
+    fn input_position(&self, pending: &str, offset: usize) -> Position {
+        let (line, column) = line_column(pending, offset);
+
+        Position {
+            offset: self.pending_position.offset + offset,
+            line: self.pending_position.line + line - 1,
+            column: if line == 1 {
+                self.pending_position.column + column - 1
+            } else {
+                column
+            },
+        }
+    }
+}
+
+impl<R: Read> Read for RepairingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(failure) = &self.failure {
+            return Err(failure.to_io_error());
+        }
+
+        while self.output_position == self.output.len() && !self.finished {
+            self.output.clear();
+            self.output_position = 0;
+            self.fill_output()?;
+        }
+
+        let available = &self.output[self.output_position..];
+        let read_len = available.len().min(buf.len());
+        buf[..read_len].copy_from_slice(&available[..read_len]);
+        self.output_position += read_len;
+
+        Ok(read_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use serde_json::{json, Value};
+
+    use crate::{
+        prepare_json_array, prepare_stringified_json_array, CommentStyle, Dialect, PrepareError,
+        PrepareErrorKind, PrepareOptions, RepairingReader, TopLevel, ValueMode,
+    };
+
+    struct OneByteReader<'a>(&'a [u8]);
+
+    impl Read for OneByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = *first;
+            self.0 = rest;
+
+            Ok(1)
+        }
+    }
+
+    /// Yields its bytes, then fails.
+    struct FailingReader<'a>(&'a [u8]);
+
+    impl Read for FailingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::other("connection reset"));
+            }
+
+            let read_len = self.0.len().min(buf.len());
+            buf[..read_len].copy_from_slice(&self.0[..read_len]);
+            self.0 = &self.0[read_len..];
+
+            Ok(read_len)
+        }
+    }
+
+    fn read_repaired(original_str: &str) -> String {
+        let mut repaired = String::new();
+        RepairingReader::new(OneByteReader(original_str.as_bytes()))
+            .read_to_string(&mut repaired)
+            .unwrap();
+
+        repaired
+    }
+
+    #[test]
+    fn matches_prepare_json_array() {
+        let original_strs = [
+            r#"[
+                {"Foo1":"BAR1", "Foo2":"BAR2", "Foo3":"BAR3"};
+                {"Foo4":"BAR4", "Foo5":"BAR5", "Foo6":"BAR6"};
+                {"Foo7":"BAR7", "Foo8":"BAR8", "Foo9":"BAR9"}
+            ]"#,
+            r#"
+                {"Foo1":"BAR1", "Foo2":"BAR2", "Foo3":"BAR3"},
+                {"Foo4":"BAR4", "Foo5": "a, b; [c", "Foo6": [1, 2]},
+            "#,
+            r#"\n[{label: "He said \"hi\", then left", groups: []}, \n]"#,
+            "{property1: lol, property2: iskrem}",
+            r#"["a, b", "x;y", "a]b", "say \"c, d\"", "ends with \\"]"#,
+            "[]",
+        ];
+
+        for original_str in original_strs {
+            assert_eq!(
+                read_repaired(original_str),
+                prepare_json_array(original_str)
+            );
+        }
+    }
+
+    #[test]
+    fn content_after_the_outer_array_is_an_error() {
+        let mut repaired = String::new();
+        let error = RepairingReader::new(OneByteReader(b"[1, 2]\n trailing, 3"))
+            .read_to_string(&mut repaired)
+            .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let prepare_error = error
+            .into_inner()
+            .and_then(|error| error.downcast::<PrepareError>().ok())
+            .unwrap();
+        assert_eq!(
+            *prepare_error,
+            PrepareError {
+                kind: PrepareErrorKind::UnexpectedContent,
+                offset: 8,
+                line: 2,
+                column: 2,
+            }
+        );
+
+        let options = PrepareOptions::new().dialect(Dialect::Json5);
+        let mut repaired = String::new();
+        RepairingReader::with_options("[1, 2] // done\n".as_bytes(), options)
+            .read_to_string(&mut repaired)
+            .unwrap();
+        assert_eq!(repaired, "[1, 2]");
+    }
+
+    #[test]
+    fn hjson_lines_are_elements() {
+        let original_str =
+            "[\n  1, 2\n  a, b # not a comment\n  {a: x, b: 1}\n  'c'\n  3 # comment\n]";
+
+        let options = PrepareOptions::new().dialect(Dialect::Hjson);
+        let mut repaired = String::new();
+        RepairingReader::with_options(OneByteReader(original_str.as_bytes()), options.clone())
+            .read_to_string(&mut repaired)
+            .unwrap();
+
+        assert_eq!(repaired, options.build().prepare(original_str));
+        assert_eq!(
+            repaired,
+            r#"[1, 2, "a, b # not a comment", {"a": "x, b: 1"}, "c", 3]"#
+        );
+    }
+
+    #[test]
+    fn keyed_php_arrays_are_read_whole() {
+        let original_strs = [
+            "[0 => 'a', 1 => 'b']",
+            "[0 => 'x', 2 => 'y']",
+            "Array\n(\n    [0] => a\n    [1] => b\n)\n",
+        ];
+
+        let options = PrepareOptions::new()
+            .dialect(Dialect::Php)
+            .top_level(TopLevel::Array);
+        for original_str in original_strs {
+            let mut repaired = String::new();
+            RepairingReader::with_options(OneByteReader(original_str.as_bytes()), options.clone())
+                .read_to_string(&mut repaired)
+                .unwrap();
+
+            assert_eq!(repaired, options.clone().build().prepare(original_str));
+        }
+
+        let mut repaired = String::new();
+        RepairingReader::with_options(original_strs[0].as_bytes(), options)
+            .read_to_string(&mut repaired)
+            .unwrap();
+        assert_eq!(repaired, r#"["a", "b"]"#);
+    }
+
+    #[test]
+    fn keyless_php_and_swift_arrays_are_streamed() {
+        let dialect_strs = [
+            (Dialect::Php, "['a', 'b', 'c', "),
+            (Dialect::Swift, r#"["a", "b", "c", "#),
+        ];
+
+        for (dialect, original_str) in dialect_strs {
+            let options = PrepareOptions::new().dialect(dialect);
+            let mut reader =
+                RepairingReader::with_options(FailingReader(original_str.as_bytes()), options);
+            let mut repaired = Vec::new();
+            let mut buf = [0; 64];
+            while let Ok(read_len) = reader.read(&mut buf) {
+                repaired.extend_from_slice(&buf[..read_len]);
+            }
+
+            assert_eq!(repaired, br#"["a", "b""#, "{dialect:?}");
+        }
+    }
+
+    #[test]
+    fn later_keys_in_a_streamed_php_array_are_errors() {
+        let options = PrepareOptions::new().dialect(Dialect::Php);
+        let mut repaired = String::new();
+        let error = RepairingReader::with_options("['x', 5 => 'y', 'z']".as_bytes(), options)
+            .read_to_string(&mut repaired)
+            .unwrap_err();
+
+        let prepare_error = error
+            .into_inner()
+            .and_then(|error| error.downcast::<PrepareError>().ok())
+            .unwrap();
+        assert_eq!(
+            *prepare_error,
+            PrepareError {
+                kind: PrepareErrorKind::UnexpectedContent,
+                offset: 8,
+                line: 1,
+                column: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn errors_are_returned_by_every_later_read() {
+        let mut reader = RepairingReader::new(OneByteReader(b"[1, 2, \xff, 3]"));
+        let mut repaired = Vec::new();
+        let error = reader.read_to_end(&mut repaired).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let mut buf = [0; 64];
+        for _ in 0..3 {
+            let error = reader.read(&mut buf).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+
+        let mut reader = RepairingReader::new(OneByteReader(b"[1] 2"));
+        let mut repaired = Vec::new();
+        reader.read_to_end(&mut repaired).unwrap_err();
+        assert!(reader.read(&mut buf).is_err());
+    }
+
+    #[test]
+    fn swift_dictionaries_are_read_whole() {
+        let original_str = r#"["a": 1, "b": 2]"#;
+
+        let options = PrepareOptions::new().dialect(Dialect::Swift);
+        let mut repaired = String::new();
+        RepairingReader::with_options(OneByteReader(original_str.as_bytes()), options)
+            .read_to_string(&mut repaired)
+            .unwrap();
+
+        assert_eq!(repaired, r#"[{"a": 1, "b": 2}]"#);
+    }
+
+    #[test]
+    fn matches_prepare_in_every_dialect() {
+        let dialect_strs = [
+            (Dialect::Json, r#"[{a: 1, b: "x, y"}; [1, 2], c d]"#),
+            (Dialect::Python, "[{'a': (1, 2)}, None, b'x', {1, 2}]"),
+            (Dialect::Python, "(1, 2)"),
+            (Dialect::JavaScript, "[{a: 'x' + 'y'}, 0x10, undefined]"),
+            (Dialect::Json5, "[1//c\n, {a: .5, /* b */}, +5, 'x']"),
+            (Dialect::Json5, "// header, [x]\n[1, 2]"),
+            (Dialect::Hjson, "[\n  1, 2\n  a, b\n  {a: 1}\n]"),
+            (Dialect::Ruby, "[{:a=>1, 'b'=>nil}, :c]"),
+            (Dialect::Perl, "$VAR1 = [{'a' => 1}, undef];"),
+            (Dialect::Php, "['a' => 1, 'b' => [true, null]]"),
+            (Dialect::Php, "['a', ['b' => 1], 'c']"),
+            (Dialect::Php, "Array\n(\n    [0] => a b\n    [1] => c\n)\n"),
+            (Dialect::Java, "[{a=1, b=[x, y]}, Device(id=5)]"),
+            (Dialect::Go, "[{A:1} {A:2 B:pump station} map[a:1] <nil>]"),
+            (Dialect::Go, "[1 2 3]"),
+            (Dialect::Rust, "[Some(1), None, Ok(2), Point { x: 1 }]"),
+            (Dialect::Swift, r#"["a": 1, "b": 2]"#),
+            (Dialect::Swift, r#"[["a": 1], [:], ("x", "y")]"#),
+        ];
+
+        for (dialect, original_str) in dialect_strs {
+            let options = PrepareOptions::new()
+                .dialect(dialect)
+                .top_level(TopLevel::Array);
+            let mut repaired = String::new();
+            RepairingReader::with_options(OneByteReader(original_str.as_bytes()), options.clone())
+                .read_to_string(&mut repaired)
+                .unwrap();
+
+            assert_eq!(
+                repaired,
+                options.build().prepare(original_str),
+                "{dialect:?}: {original_str}"
+            );
+        }
+    }
+
+    #[test]
+    fn feeds_serde_json_from_reader() {
+        let original_str = "[{id: 1, tags: [a, b]}; {id: 2, tags: []};]";
+
+        let reader = RepairingReader::new(original_str.as_bytes());
+        let json_value = serde_json::from_reader::<_, Value>(reader).unwrap();
+
+        assert_eq!(
+            json_value,
+            json!([{"id": 1, "tags": ["a", "b"]}, {"id": 2, "tags": []}])
+        );
+    }
+
+    #[test]
+    fn unclosed_brackets_do_not_swallow_following_elements() {
+        let original_str = "[{a: [1, 2}, {a: 3}]";
+
+        let repaired = read_repaired(original_str);
+
+        assert_eq!(repaired, r#"[{"a": [1, 2]}, {"a": 3}]"#);
+    }
+
+    #[test]
+    fn with_options_matches_stringified_array() {
+        let original_str = "{id: 1, on: true}; {id: 2, on: false}";
+
+        let options = PrepareOptions::new().value_mode(ValueMode::Stringified);
+        let mut repaired = String::new();
+        RepairingReader::with_options(original_str.as_bytes(), options)
+            .read_to_string(&mut repaired)
+            .unwrap();
+
+        assert_eq!(repaired, prepare_stringified_json_array(original_str));
+    }
+
+    #[test]
+    fn with_options_follows_the_dialect() {
+        let original_str = "[{'a': (1, 2), 'b': 'x, y'}, {'a': None}]";
+
+        let options = PrepareOptions::new().dialect(Dialect::Python);
+        let mut repaired = String::new();
+        RepairingReader::with_options(original_str.as_bytes(), options.clone())
+            .read_to_string(&mut repaired)
+            .unwrap();
+
+        assert_eq!(repaired, options.build().prepare(original_str));
+        assert_eq!(repaired, r#"[{"a": [1, 2], "b": "x, y"}, {"a": null}]"#);
+    }
+
+    #[test]
+    fn comments_do_not_split_elements() {
+        let original_str = "[1, // two, three\n{a: 4 /* ], 5 */}, 'x#y' # z, w\n]";
+
+        let options = PrepareOptions::new().dialect(Dialect::Json5).comments(&[
+            CommentStyle::DoubleSlash,
+            CommentStyle::SlashStar,
+            CommentStyle::Hash,
+        ]);
+        let mut repaired = String::new();
+        RepairingReader::with_options(original_str.as_bytes(), options.clone())
+            .read_to_string(&mut repaired)
+            .unwrap();
+
+        assert_eq!(repaired, options.build().prepare(original_str));
+        assert_eq!(repaired, r#"[1, {"a": 4}, "x#y"]"#);
+    }
+
+    #[test]
+    fn dialect_comments_may_follow_values() {
+        let original_str = "[1//c, 2\n, {a: 'x'/* ], */}, 3#4]";
+
+        let options = PrepareOptions::new().dialect(Dialect::Json5);
+        let mut repaired = String::new();
+        RepairingReader::with_options(original_str.as_bytes(), options.clone())
+            .read_to_string(&mut repaired)
+            .unwrap();
+
+        assert_eq!(repaired, options.build().prepare(original_str));
+        assert_eq!(repaired, r#"[1, {"a": "x"}, "3#4"]"#);
+    }
+}
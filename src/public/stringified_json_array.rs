@@ -1,28 +1,34 @@
-use crate::{
-    helpers::{content_str, ensure_array_wrapper, json_context, rewrap_string},
-    public::parse_stringified_json_string::parse_stringified_json_string,
+use crate::public::{
+    prepare_error::PrepareError,
+    prepare_options::{PrepareOptions, TopLevel, ValueMode},
+    preparer::Preparer,
 };
 
+fn stringified_array_preparer() -> Preparer {
+    PrepareOptions::new()
+        .top_level(TopLevel::Array)
+        .value_mode(ValueMode::Stringified)
+        .build()
+}
+
 pub fn prepare_stringified_json_array(original_str: &str) -> String {
-    let trimmed_str = original_str
-        .trim_matches([' ', '\n', '\t', ',', ';', ':'])
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .trim_start_matches("\\n")
-        .trim_end_matches("\\n");
-    let array_input = ensure_array_wrapper(trimmed_str);
-    let json_context = json_context(&array_input);
-    let content_str = content_str(json_context.clone(), &array_input);
-
-    let parsed_json_string = parse_stringified_json_string(&content_str, json_context.clone());
-
-    let rewrapped_string = rewrap_string(&parsed_json_string, json_context);
-    rewrapped_string
+    let prepared_string = stringified_array_preparer().prepare(original_str);
+
+    prepared_string
+}
+
+pub fn try_prepare_stringified_json_array(original_str: &str) -> Result<String, PrepareError> {
+    let prepared_string = stringified_array_preparer().try_prepare(original_str)?;
+
+    Ok(prepared_string)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::public::stringified_json_array::prepare_stringified_json_array;
+    use crate::{
+        public::stringified_json_array::prepare_stringified_json_array,
+        try_prepare_stringified_json_array, PrepareErrorKind,
+    };
 
     #[test]
     fn comma_inside_value() {
@@ -101,8 +107,18 @@ mod tests {
 
         let prepared_str = prepare_stringified_json_array(original_str);
 
-        let expected_str = r#"[{[{"unit_id": "5", "ec_id": "0", "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]}]"#.to_string();
+        let expected_str = r#"[{"unit_id": "5", "ec_id": "0", "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]"#.to_string();
 
         assert_eq!(prepared_str, expected_str);
     }
+
+    #[test]
+    fn try_prepare_stringified_json_array_stray_closer() {
+        let original_str = "[1, 2]], 3";
+
+        let error = try_prepare_stringified_json_array(original_str).unwrap_err();
+
+        assert_eq!(error.kind, PrepareErrorKind::UnbalancedBracket);
+        assert_eq!(error.offset, 6);
+    }
 }
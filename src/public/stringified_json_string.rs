@@ -1,27 +1,32 @@
-use crate::{
-    helpers::{content_str, json_context, rewrap_string},
-    public::parse_stringified_json_string::parse_stringified_json_string,
+use crate::public::{
+    prepare_error::PrepareError,
+    prepare_options::{PrepareOptions, ValueMode},
+    preparer::Preparer,
 };
 
+fn stringified_preparer() -> Preparer {
+    PrepareOptions::new()
+        .value_mode(ValueMode::Stringified)
+        .build()
+}
+
 pub fn prepare_stringified_json_string(original_str: &str) -> String {
-    let trimmed_str = original_str
-        .trim_matches([' ', '\n', '\t', ',', ';', ':'])
-        .trim_start_matches("\\\\n")
-        .trim_end_matches("\\\\n")
-        .trim_start_matches("\\n")
-        .trim_end_matches("\\n");
-    let json_context = json_context(trimmed_str);
-    let content_str = content_str(json_context.clone(), trimmed_str);
-
-    let parsed_json_string = parse_stringified_json_string(&content_str, json_context.clone());
-
-    let rewrapped_string = rewrap_string(&parsed_json_string, json_context);
-    rewrapped_string
+    let prepared_string = stringified_preparer().prepare(original_str);
+
+    prepared_string
+}
+
+pub fn try_prepare_stringified_json_string(original_str: &str) -> Result<String, PrepareError> {
+    let prepared_string = stringified_preparer().try_prepare(original_str)?;
+
+    Ok(prepared_string)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::prepare_stringified_json_string;
+    use crate::{
+        prepare_stringified_json_string, try_prepare_stringified_json_string, PrepareErrorKind,
+    };
 
     #[test]
     fn comma_inside_value() {
@@ -100,8 +105,23 @@ mod tests {
 
         let prepared_str = prepare_stringified_json_string(original_str);
 
-        let expected_str = r#"{[{"unit_id": "5", "ec_id": "0", "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]}"#.to_string();
+        let expected_str = r#"[{"unit_id": "5", "ec_id": "0", "label": "SOMETHING", "uid": "00000000-0000-0000-0000-000000000001", "customtags": {"tag1": ""}, "groups": ["input:max_adr:205"]}]"#.to_string();
 
         assert_eq!(prepared_str, expected_str);
     }
+
+    #[test]
+    fn try_prepare_stringified_json_string_keyless_value() {
+        let original_str = "{[1, 2]}";
+
+        let prepared_str = try_prepare_stringified_json_string(original_str).unwrap();
+        assert_eq!(prepared_str, r#"["1", "2"]"#);
+
+        let original_str = "{a: 1, [1, 2]}";
+
+        let error = try_prepare_stringified_json_string(original_str).unwrap_err();
+
+        assert_eq!(error.kind, PrepareErrorKind::KeylessValue);
+        assert_eq!(error.offset, 7);
+    }
 }
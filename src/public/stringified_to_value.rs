@@ -0,0 +1,56 @@
+use serde_json::Value;
+
+use crate::public::{
+    prepare_error::PrepareError,
+    prepare_options::{PrepareOptions, TopLevel, ValueMode},
+};
+
+pub fn prepare_stringified_to_value(original_str: &str) -> Result<Value, PrepareError> {
+    let json_value = PrepareOptions::new()
+        .value_mode(ValueMode::Stringified)
+        .build()
+        .prepare_to_value(original_str)?;
+
+    Ok(json_value)
+}
+
+pub fn prepare_stringified_array_to_value(original_str: &str) -> Result<Value, PrepareError> {
+    let json_value = PrepareOptions::new()
+        .top_level(TopLevel::Array)
+        .value_mode(ValueMode::Stringified)
+        .build()
+        .prepare_to_value(original_str)?;
+
+    Ok(json_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{prepare_stringified_array_to_value, prepare_stringified_to_value};
+
+    #[test]
+    fn leaves_become_strings() {
+        let original_str = "{property1: 17, property2: iskrem, property3: [true, None]}";
+
+        let json_value = prepare_stringified_to_value(original_str).unwrap();
+
+        let expected_value =
+            json!({"property1": "17", "property2": "iskrem", "property3": ["true", "None"]});
+
+        assert_eq!(json_value, expected_value);
+    }
+
+    #[test]
+    fn prepare_stringified_array_to_value_wraps_objects() {
+        let original_str = r#"{"Foo1":19, "Foo2":"BAR2"}, {"Foo4":10};"#;
+
+        let json_value = prepare_stringified_array_to_value(original_str).unwrap();
+
+        assert_eq!(
+            json_value,
+            json!([{"Foo1": "19", "Foo2": "BAR2"}, {"Foo4": "10"}])
+        );
+    }
+}
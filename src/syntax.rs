@@ -0,0 +1,1169 @@
+use crate::public::prepare_options::{CommentStyle, Dialect};
+
+/// Escape sequences understood inside quoted strings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Escapes {
+    Json,
+    Python,
+    JavaScript,
+    /// Ruby, Perl and PHP: single-quoted strings only escape `\\` and `\'`, double-quoted ones
+    /// use C escapes.
+    Ruby,
+}
+
+/// The lexical and structural rules of a dialect, looked up once per parse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct Syntax {
+    /// Characters that open and close a quoted string.
+    pub(crate) quotes: &'static [u8],
+    /// Letters that may prefix a quoted string, such as the `b` in `b'..'`.
+    pub(crate) string_prefixes: &'static [u8],
+    pub(crate) escapes: Escapes,
+    /// Characters between a key and its value, `:` in most dialects.
+    pub(crate) pair_separators: &'static [u8],
+    /// Whether `(` and `)` delimit tuples rather than being part of bare text.
+    pub(crate) parens: bool,
+    /// Whether `name(...)` is a call whose arguments stand in for the value.
+    pub(crate) calls: bool,
+    /// Whether a brace body without any `key: value` pair is a set, rendered as an array.
+    pub(crate) sets: bool,
+    /// Spellings of null in the dialect, which are never treated as invalid literals.
+    pub(crate) null_literals: &'static [&'static str],
+    /// Whether `0x`, `0o` and `0b` integers are numbers.
+    pub(crate) radix_numbers: bool,
+    /// Whether strings joined with `+` are concatenated.
+    pub(crate) concatenation: bool,
+    pub(crate) comments: &'static [CommentStyle],
+    /// Whether non-ASCII whitespace such as `U+00A0` and `U+FEFF` separates tokens.
+    pub(crate) unicode_whitespace: bool,
+    /// Whether a line break separates elements and members like a `,` does.
+    pub(crate) newline_separators: bool,
+    /// Whether values that start without a quote or bracket run to the end of the line.
+    pub(crate) quoteless_strings: bool,
+    /// Whether `'''` opens a multi-line string whose indentation is stripped.
+    pub(crate) multiline_strings: bool,
+    /// Whether `=>` separates keys from values like `:` does.
+    pub(crate) fat_arrows: bool,
+    /// Whether `:name` is a symbol, read as the string `name`.
+    pub(crate) symbols: bool,
+    /// Whether a leading `$name =` assignment is dropped, along with its trailing `;`.
+    pub(crate) assignments: bool,
+    /// Whether `array(...)`, `[...]` and `print_r` blocks are PHP arrays, which become arrays
+    /// or objects depending on their keys.
+    pub(crate) php_arrays: bool,
+    /// Whether `Name(...)`, `Name[...]` and `Name {...}` holding `key = value` pairs are
+    /// objects whose type name is dropped or kept under the `type_key` option.
+    pub(crate) named_objects: bool,
+    /// Whether whitespace inside brackets separates elements, and in bodies holding
+    /// `key: value` pairs, members, which starts where a `key:` follows the whitespace.
+    pub(crate) whitespace_separators: bool,
+    /// Whether `map[...]` is an object and the `&` before a pointer's `{` or `[` is dropped.
+    pub(crate) go_values: bool,
+    /// Whether Rust `Debug` forms are read: `Some(x)` is `x`, other `Name(x)` forms are tagged
+    /// objects, `()` is null and `vec![..]` is an array.
+    pub(crate) rust_values: bool,
+    /// Whether a bracket body holding `key: value` pairs, or `[:]`, is a dictionary.
+    pub(crate) dictionaries: bool,
+    /// Whether Python constructor calls are read: `dict(..)`, `OrderedDict(..)` and calls with
+    /// `key=value` arguments are objects, and calls with several positional arguments, such as
+    /// `datetime.date(2024, 1, 2)`, stay strings.
+    pub(crate) python_values: bool,
+    /// Whether a member holding both pair separators is split at the `=`, so `urn:a=1` has the
+    /// key `urn:a`.
+    pub(crate) equals_first: bool,
+    /// Characters that quote a string only around a whole value, as in `name='Ann'`.
+    pub(crate) value_quotes: &'static [u8],
+}
+
+impl Syntax {
+    const JSON: Syntax = Syntax {
+        quotes: b"\"",
+        string_prefixes: b"",
+        escapes: Escapes::Json,
+        pair_separators: b":",
+        parens: false,
+        calls: false,
+        sets: false,
+        null_literals: &[],
+        radix_numbers: false,
+        concatenation: false,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: false,
+        symbols: false,
+        assignments: false,
+        php_arrays: false,
+        named_objects: false,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: false,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const PYTHON: Syntax = Syntax {
+        quotes: b"\"'",
+        string_prefixes: b"bBrRuUfF",
+        escapes: Escapes::Python,
+        pair_separators: b":=",
+        parens: true,
+        calls: true,
+        sets: true,
+        null_literals: &["None"],
+        radix_numbers: false,
+        concatenation: false,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: false,
+        symbols: false,
+        assignments: false,
+        php_arrays: false,
+        named_objects: false,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: false,
+        python_values: true,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const JAVASCRIPT: Syntax = Syntax {
+        quotes: b"\"'`",
+        string_prefixes: b"",
+        escapes: Escapes::JavaScript,
+        pair_separators: b":",
+        parens: false,
+        calls: false,
+        sets: false,
+        null_literals: &[],
+        radix_numbers: true,
+        concatenation: true,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: false,
+        symbols: false,
+        assignments: false,
+        php_arrays: false,
+        named_objects: false,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: false,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const JSON5: Syntax = Syntax {
+        quotes: b"\"'",
+        string_prefixes: b"",
+        escapes: Escapes::JavaScript,
+        pair_separators: b":",
+        parens: false,
+        calls: false,
+        sets: false,
+        null_literals: &[],
+        radix_numbers: true,
+        concatenation: false,
+        comments: &[CommentStyle::DoubleSlash, CommentStyle::SlashStar],
+        unicode_whitespace: true,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: false,
+        symbols: false,
+        assignments: false,
+        php_arrays: false,
+        named_objects: false,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: false,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const HJSON: Syntax = Syntax {
+        quotes: b"\"'",
+        string_prefixes: b"",
+        escapes: Escapes::JavaScript,
+        pair_separators: b":",
+        parens: false,
+        calls: false,
+        sets: false,
+        null_literals: &[],
+        radix_numbers: false,
+        concatenation: false,
+        comments: &[
+            CommentStyle::Hash,
+            CommentStyle::DoubleSlash,
+            CommentStyle::SlashStar,
+        ],
+        unicode_whitespace: false,
+        newline_separators: true,
+        quoteless_strings: true,
+        multiline_strings: true,
+        fat_arrows: false,
+        symbols: false,
+        assignments: false,
+        php_arrays: false,
+        named_objects: false,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: false,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const RUBY: Syntax = Syntax {
+        quotes: b"\"'",
+        string_prefixes: b"",
+        escapes: Escapes::Ruby,
+        pair_separators: b":",
+        parens: false,
+        calls: false,
+        sets: false,
+        null_literals: &["nil"],
+        radix_numbers: true,
+        concatenation: false,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: true,
+        symbols: true,
+        assignments: false,
+        php_arrays: false,
+        named_objects: false,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: false,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const PERL: Syntax = Syntax {
+        quotes: b"\"'",
+        string_prefixes: b"",
+        escapes: Escapes::Ruby,
+        pair_separators: b":",
+        parens: false,
+        calls: false,
+        sets: false,
+        null_literals: &["undef"],
+        radix_numbers: true,
+        concatenation: false,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: true,
+        symbols: false,
+        assignments: true,
+        php_arrays: false,
+        named_objects: false,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: false,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const PHP: Syntax = Syntax {
+        quotes: b"\"'",
+        string_prefixes: b"",
+        escapes: Escapes::Ruby,
+        pair_separators: b":",
+        parens: true,
+        calls: true,
+        sets: false,
+        null_literals: &[],
+        radix_numbers: true,
+        concatenation: false,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: true,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: true,
+        symbols: false,
+        assignments: false,
+        php_arrays: true,
+        named_objects: false,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: false,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const JAVA: Syntax = Syntax {
+        quotes: b"\"",
+        string_prefixes: b"",
+        escapes: Escapes::Json,
+        pair_separators: b":=",
+        parens: true,
+        calls: false,
+        sets: false,
+        null_literals: &[],
+        radix_numbers: false,
+        concatenation: false,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: false,
+        symbols: false,
+        assignments: false,
+        php_arrays: false,
+        named_objects: true,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: false,
+        python_values: false,
+        equals_first: true,
+        value_quotes: b"'",
+    };
+
+    const GO: Syntax = Syntax {
+        quotes: b"\"",
+        string_prefixes: b"",
+        escapes: Escapes::Json,
+        pair_separators: b":",
+        parens: false,
+        calls: false,
+        sets: true,
+        null_literals: &["<nil>"],
+        radix_numbers: false,
+        concatenation: false,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: false,
+        symbols: false,
+        assignments: false,
+        php_arrays: false,
+        named_objects: false,
+        whitespace_separators: true,
+        go_values: true,
+        rust_values: false,
+        dictionaries: false,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const RUST: Syntax = Syntax {
+        quotes: b"\"'",
+        string_prefixes: b"",
+        escapes: Escapes::JavaScript,
+        pair_separators: b":",
+        parens: true,
+        calls: false,
+        sets: true,
+        null_literals: &["None"],
+        radix_numbers: false,
+        concatenation: false,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: false,
+        symbols: false,
+        assignments: false,
+        php_arrays: false,
+        named_objects: true,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: true,
+        dictionaries: false,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    const SWIFT: Syntax = Syntax {
+        quotes: b"\"",
+        string_prefixes: b"",
+        escapes: Escapes::JavaScript,
+        pair_separators: b":=",
+        parens: true,
+        calls: false,
+        sets: false,
+        null_literals: &["nil", "<null>"],
+        radix_numbers: false,
+        concatenation: false,
+        comments: &[],
+        unicode_whitespace: false,
+        newline_separators: false,
+        quoteless_strings: false,
+        multiline_strings: false,
+        fat_arrows: false,
+        symbols: false,
+        assignments: false,
+        php_arrays: false,
+        named_objects: true,
+        whitespace_separators: false,
+        go_values: false,
+        rust_values: false,
+        dictionaries: true,
+        python_values: false,
+        equals_first: false,
+        value_quotes: b"",
+    };
+
+    pub(crate) fn is_quote(&self, byte: u8) -> bool {
+        self.quotes.contains(&byte)
+    }
+
+    pub(crate) fn is_pair_separator(&self, byte: u8) -> bool {
+        self.pair_separators.contains(&byte)
+    }
+
+    pub(crate) fn is_paren(&self, byte: u8) -> bool {
+        self.parens && matches!(byte, b'(' | b')')
+    }
+
+    /// Length of a string prefix at the start of `bytes`, or 0 if `bytes` does not start
+    /// with up to two prefix letters followed by a quote.
+    pub(crate) fn string_prefix_len(&self, bytes: &[u8]) -> usize {
+        let prefix_len = bytes
+            .iter()
+            .take(2)
+            .take_while(|byte| self.string_prefixes.contains(byte))
+            .count();
+
+        (1..=prefix_len)
+            .find(|&len| bytes.get(len).is_some_and(|&byte| self.is_quote(byte)))
+            .unwrap_or(0)
+    }
+}
+
+impl Dialect {
+    pub(crate) fn syntax(self) -> Syntax {
+        let syntax = match self {
+            Dialect::Json => Syntax::JSON,
+            Dialect::Python => Syntax::PYTHON,
+            Dialect::JavaScript => Syntax::JAVASCRIPT,
+            Dialect::Json5 => Syntax::JSON5,
+            Dialect::Hjson => Syntax::HJSON,
+            Dialect::Ruby => Syntax::RUBY,
+            Dialect::Perl => Syntax::PERL,
+            Dialect::Php => Syntax::PHP,
+            Dialect::Java => Syntax::JAVA,
+            Dialect::Go => Syntax::GO,
+            Dialect::Rust => Syntax::RUST,
+            Dialect::Swift => Syntax::SWIFT,
+        };
+
+        syntax
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use serde_json::{json, Value};
+
+    use crate::{Dialect, PrepareErrorKind, PrepareOptions, ValueMode};
+
+    fn prepare(dialect: Dialect, original_str: &str) -> String {
+        PrepareOptions::new()
+            .dialect(dialect)
+            .build()
+            .prepare(original_str)
+    }
+
+    #[test]
+    fn long_single_line_input() {
+        let elements = (0..40_000)
+            .map(|index| format!("{{\"id\": {index}}}"))
+            .collect::<Vec<_>>();
+        let original_str = format!("[{}]", elements.join(", "));
+
+        for dialect in [Dialect::Json, Dialect::Json5, Dialect::Hjson] {
+            let prepared_str = prepare(dialect, &original_str);
+
+            assert_eq!(prepared_str, original_str, "{dialect:?}");
+        }
+    }
+
+    #[test]
+    fn python_repr() {
+        let original_str = r#"{'a': True, 'b': None, 'c': (1, 2), "d": 'it\'s', 'e': False}"#;
+
+        let prepared_str = prepare(Dialect::Python, original_str);
+
+        let expected_str = r#"{"a": true, "b": null, "c": [1, 2], "d": "it's", "e": false}"#;
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn python_strings() {
+        let original_str = r#"['tab\there', "q\"uote", b'\x00\xffraw', r'C:\temp', u'caf\xe9', 'snow\u2603', '\N{DASH}', 'oct\101']"#;
+
+        let json_value = PrepareOptions::new()
+            .dialect(Dialect::Python)
+            .build()
+            .prepare_to_value(original_str)
+            .unwrap();
+
+        assert_eq!(
+            json_value,
+            json!([
+                "tab\there",
+                "q\"uote",
+                "\u{0}\u{ff}raw",
+                r"C:\temp",
+                "café",
+                "snow☃",
+                r"\N{DASH}",
+                "octA"
+            ])
+        );
+    }
+
+    #[test]
+    fn python_tuples_sets_and_calls() {
+        let original_strs = [
+            ("(1,)", "[1]"),
+            ("()", "[]"),
+            ("{1, 'a'}", r#"[1, "a"]"#),
+            ("{}", "{}"),
+            ("set()", "[]"),
+            ("frozenset({1, 2})", "[1, 2]"),
+            ("{'k': {(1, 2), (3, 4)}}", r#"{"k": [[1, 2], [3, 4]]}"#),
+            ("{'price': Decimal('1.50')}", r#"{"price": "1.50"}"#),
+            ("[nan, inf, None]", "[null, null, null]"),
+            (
+                "OrderedDict([('a', 1), ('b', [2])])",
+                r#"{"a": 1, "b": [2]}"#,
+            ),
+            ("dict(a=1, b='x')", r#"{"a": 1, "b": "x"}"#),
+            ("dict()", "{}"),
+            ("defaultdict(<class 'list'>, {'a': [1]})", r#"{"a": [1]}"#),
+            ("Point(x=1, y=2)", r#"{"x": 1, "y": 2}"#),
+            (
+                "{'at': datetime.datetime(2024, 1, 2, 3, 4)}",
+                r#"{"at": "datetime.datetime(2024, 1, 2, 3, 4)"}"#,
+            ),
+        ];
+
+        for (original_str, expected_str) in original_strs {
+            assert_eq!(prepare(Dialect::Python, original_str), expected_str);
+        }
+    }
+
+    #[test]
+    fn python_keyword_calls_keep_their_type_name() {
+        let json_value = PrepareOptions::new()
+            .dialect(Dialect::Python)
+            .type_key("__type__")
+            .build()
+            .prepare_to_value("Point(x=1, y=2, tags=dict(a=1))")
+            .unwrap();
+
+        assert_eq!(
+            json_value,
+            json!({"__type__": "Point", "x": 1, "y": 2, "tags": {"a": 1}})
+        );
+    }
+
+    #[test]
+    fn python_none_is_null_even_when_stringified() {
+        let preparer = PrepareOptions::new()
+            .dialect(Dialect::Python)
+            .value_mode(ValueMode::Stringified)
+            .build();
+
+        assert_eq!(
+            preparer.prepare("{'a': None, 'b': 1}"),
+            r#"{"a": "null", "b": "1"}"#
+        );
+    }
+
+    #[test]
+    fn json_dialect_keeps_single_quotes_and_parens_as_text() {
+        let prepared_str = prepare(Dialect::Json, "{a: it's (UPS 1), b: 'x'}");
+
+        assert_eq!(prepared_str, r#"{"a": "it's (UPS 1)", "b": "'x'"}"#);
+    }
+
+    #[test]
+    fn javascript_object_literal() {
+        let original_str = r#"{
+            $id: 'abc',
+            _private: "x",
+            label: `multi
+line`,
+            mask: 0xFF,
+            flags: -0b1_01,
+            perms: 0o755,
+            missing: undefined,
+            greeting: 'Hello, ' + "world" + '!',
+            count: 'n=' + 3,
+            math: 1 + 2,
+            nested: [ 'a', `b`, ],
+        }"#;
+
+        let json_value = PrepareOptions::new()
+            .dialect(Dialect::JavaScript)
+            .build()
+            .prepare_to_value(original_str)
+            .unwrap();
+
+        assert_eq!(
+            json_value,
+            json!({
+                "$id": "abc",
+                "_private": "x",
+                "label": "multi\nline",
+                "mask": 255,
+                "flags": -5,
+                "perms": 493,
+                "missing": null,
+                "greeting": "Hello, world!",
+                "count": "n=3",
+                "math": "1 + 2",
+                "nested": ["a", "b"]
+            })
+        );
+    }
+
+    #[test]
+    fn javascript_escapes() {
+        let original_str = r"['it\'s', 'tab\x09', '\u{1F600}', '\0', 'line\
+continued', '\q', `tick\``]";
+
+        let prepared_str = prepare(Dialect::JavaScript, original_str);
+
+        let expected_str = r#"["it's", "tab\t", "😀", "\u0000", "linecontinued", "q", "tick`"]"#;
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn javascript_single_quotes_are_not_double_wrapped() {
+        assert_eq!(
+            prepare(Dialect::JavaScript, "{a: 'abc'}"),
+            r#"{"a": "abc"}"#
+        );
+        assert_eq!(prepare(Dialect::Json, "{a: 'abc'}"), r#"{"a": "'abc'"}"#);
+    }
+
+    #[test]
+    fn json5_conformance() {
+        let preparer = PrepareOptions::new().dialect(Dialect::Json5).build();
+        let cases_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/json5");
+        let mut case_count = 0;
+
+        for category in fs::read_dir(cases_dir).unwrap() {
+            let category_path = category.unwrap().path();
+            if !category_path.is_dir() {
+                continue;
+            }
+
+            for case in fs::read_dir(category_path).unwrap() {
+                let case_path = case.unwrap().path();
+                if case_path
+                    .extension()
+                    .is_none_or(|extension| extension != "json5")
+                {
+                    continue;
+                }
+
+                let original_str = fs::read_to_string(&case_path).unwrap();
+                let expected_str = fs::read_to_string(case_path.with_extension("json")).unwrap();
+
+                let prepared_str = preparer.prepare(&original_str);
+
+                let prepared_value = serde_json::from_str::<Value>(&prepared_str)
+                    .unwrap_or_else(|error| panic!("{case_path:?}: {prepared_str}: {error}"));
+                let expected_value = serde_json::from_str::<Value>(&expected_str).unwrap();
+                assert_eq!(prepared_value, expected_value, "{case_path:?}");
+                case_count += 1;
+            }
+        }
+
+        assert!(case_count > 30);
+    }
+
+    #[test]
+    fn json5_comments_and_numbers() {
+        let original_str = "{
+            // a comment with a, separator
+            url: http://example.com/a, /* block
+            comment */ b: [.5, 5., +5, 0xA], c: 'it\\'s' // trailing
+        }";
+
+        let prepared_str = prepare(Dialect::Json5, original_str);
+
+        let expected_str =
+            r#"{"url": "http://example.com/a", "b": [0.5, 5.0, 5, 10], "c": "it's"}"#;
+
+        assert_eq!(prepared_str, expected_str);
+        assert_eq!(prepare(Dialect::Json, "{a: x // y}"), r#"{"a": "x // y"}"#);
+    }
+
+    #[test]
+    fn leading_zero_numbers_stay_strings() {
+        for dialect in [Dialect::Json, Dialect::Json5, Dialect::Python, Dialect::Go] {
+            let prepared_str = prepare(dialect, "{zip: 007, id: -01.5, n: 0, x: 0.5, e: 0e1}");
+
+            assert_eq!(
+                prepared_str, r#"{"zip": "007", "id": "-01.5", "n": 0, "x": 0.5, "e": 0e1}"#,
+                "{dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn comments_after_values() {
+        let original_strs = [
+            ("[1//c\n]", "[1]"),
+            ("{a:1/*c*/}", r#"{"a": 1}"#),
+            ("{a: true// c\n, b: 'x'/* c */}", r#"{"a": true, "b": "x"}"#),
+        ];
+
+        for dialect in [Dialect::Json5, Dialect::Hjson] {
+            for (original_str, expected_str) in original_strs {
+                assert_eq!(prepare(dialect, original_str), expected_str, "{dialect:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn hjson_config() {
+        let original_str = "{
+  # unit settings
+  name: Pump station, north side
+  unit_id: 5 // decimal
+  enabled: true
+  ratio: 0.5,
+  url: http://example.com/#status
+  quoted: \"a, b\"
+  tags: [
+    first tag
+    2
+    'third'
+  ]
+  notes:
+    '''
+    line one
+      indented
+    '''
+  /* block
+     comment */
+  inline: {a: b}
+}";
+
+        let json_value = PrepareOptions::new()
+            .dialect(Dialect::Hjson)
+            .build()
+            .prepare_to_value(original_str)
+            .unwrap();
+
+        assert_eq!(
+            json_value,
+            json!({
+                "name": "Pump station, north side",
+                "unit_id": 5,
+                "enabled": true,
+                "ratio": 0.5,
+                "url": "http://example.com/#status",
+                "quoted": "a, b",
+                "tags": ["first tag", 2, "third"],
+                "notes": "line one\n  indented",
+                "inline": {"a": "b"}
+            })
+        );
+    }
+
+    #[test]
+    fn hjson_without_root_braces() {
+        let original_str = "# comment\nhost: example.com\nport: 8080\nmotd: it's # not a comment\n";
+
+        assert_eq!(
+            prepare(Dialect::Hjson, original_str),
+            r#"{"host": "example.com", "port": 8080, "motd": "it's # not a comment"}"#
+        );
+    }
+
+    #[test]
+    fn ruby_inspect() {
+        let original_str = r#"{:name=>"x\ty", "id"=>5, :tags=>[:a, :"b c"], :nothing=>nil, 'path'=>'C:\temp\'s', style: :new}"#;
+
+        let json_value = PrepareOptions::new()
+            .dialect(Dialect::Ruby)
+            .build()
+            .prepare_to_value(original_str)
+            .unwrap();
+
+        assert_eq!(
+            json_value,
+            json!({
+                "name": "x\ty",
+                "id": 5,
+                "tags": ["a", "b c"],
+                "nothing": null,
+                "path": "C:\\temp's",
+                "style": "new"
+            })
+        );
+    }
+
+    #[test]
+    fn perl_data_dumper() {
+        let original_str = "$VAR1 = {
+          'name' => 'x',
+          'id' => 5,
+          'tags' => [
+                      'a',
+                      'b'
+                    ],
+          'missing' => undef,
+          'nested' => { 'k' => 'it\\'s' }
+        };";
+
+        let prepared_str = prepare(Dialect::Perl, original_str);
+
+        let expected_str = r#"{"name": "x", "id": 5, "tags": ["a", "b"], "missing": null, "nested": {"k": "it's"}}"#;
+
+        assert_eq!(prepared_str, expected_str);
+        assert_eq!(prepare(Dialect::Perl, "$VAR1 = 'x';"), r#""x""#);
+    }
+
+    #[test]
+    fn php_var_export() {
+        let original_str = r"array (
+  'a' => 1,
+  'b' => 
+  array (
+    0 => 'x',
+    1 => 'it\'s',
+  ),
+  'c' => NULL,
+  'd' => 
+  \Foo::__set_state(array(
+     'id' => 7,
+  )),
+  'e' => 
+  (object) array(
+     'on' => true,
+  ),
+  'f' => 
+  array (
+    1 => 'one',
+  ),
+)";
+
+        let json_value = PrepareOptions::new()
+            .dialect(Dialect::Php)
+            .build()
+            .prepare_to_value(original_str)
+            .unwrap();
+
+        assert_eq!(
+            json_value,
+            json!({
+                "a": 1,
+                "b": ["x", "it's"],
+                "c": null,
+                "d": {"id": 7},
+                "e": {"on": true},
+                "f": {"1": "one"}
+            })
+        );
+    }
+
+    #[test]
+    fn php_print_r() {
+        let original_str = "Array
+(
+    [name] => Pump station, north
+    [tags] => Array
+        (
+            [0] => a
+            [1] => b c
+        )
+
+    [owner] => stdClass Object
+        (
+            [id] => 5
+        )
+
+    [empty] => 
+)
+";
+
+        let prepared_str = prepare(Dialect::Php, original_str);
+
+        let expected_str = r#"{"name": "Pump station, north", "tags": ["a", "b c"], "owner": {"id": 5}, "empty": null}"#;
+
+        assert_eq!(prepared_str, expected_str);
+    }
+
+    #[test]
+    fn php_print_r_on_one_line() {
+        let original_strs = [
+            ("Array ( [a] => 1 )", r#"{"a": 1}"#),
+            ("Array ( [0] => a [1] => b )", r#"["a", "b"]"#),
+            (
+                "Array ( [name] => Pump (UPS 1) [tags] => Array ( [0] => x ) [owner] => stdClass Object ( [id] => 5 ) )",
+                r#"{"name": "Pump (UPS 1)", "tags": ["x"], "owner": {"id": 5}}"#,
+            ),
+        ];
+
+        for (original_str, expected_str) in original_strs {
+            let prepared_str = PrepareOptions::new()
+                .dialect(Dialect::Php)
+                .build()
+                .try_prepare(original_str);
+
+            assert_eq!(prepared_str.unwrap(), expected_str);
+        }
+    }
+
+    #[test]
+    fn php_short_arrays() {
+        let original_strs = [
+            ("[1, 2, 3]", "[1, 2, 3]"),
+            (
+                "['a' => 1, 'b' => [true, null]]",
+                r#"{"a": 1, "b": [true, null]}"#,
+            ),
+            ("[0 => 'x', 2 => 'y']", r#"{"0": "x", "2": "y"}"#),
+            ("['x', 5 => 'y', 'z']", r#"{"0": "x", "5": "y", "6": "z"}"#),
+            ("[]", "[]"),
+        ];
+
+        for (original_str, expected_str) in original_strs {
+            assert_eq!(prepare(Dialect::Php, original_str), expected_str);
+        }
+    }
+
+    #[test]
+    fn java_to_string() {
+        let original_strs = [
+            ("{a=1, b=[x, y]}", r#"{"a": 1, "b": ["x", "y"]}"#),
+            (
+                "Device(id=5, label=Pump 1, owner=Person(name=Ann))",
+                r#"{"id": 5, "label": "Pump 1", "owner": {"name": "Ann"}}"#,
+            ),
+            (
+                "Device { Id = 5, Label = Pump 1 }",
+                r#"{"Id": 5, "Label": "Pump 1"}"#,
+            ),
+            (
+                "Device[id=5, opened=12:30]",
+                r#"{"id": 5, "opened": "12:30"}"#,
+            ),
+            ("Reading(value=Optional[5])", r#"{"value": 5}"#),
+            ("Empty()", "{}"),
+            (r#"{"a": 1, b=[x]}"#, r#"{"a": 1, "b": ["x"]}"#),
+            ("{urn:a=1, b=2, c:3}", r#"{"urn:a": 1, "b": 2, "c": 3}"#),
+            (
+                "Person{name='Ann', nick='A, B', note=it's, id='7'}",
+                r#"{"name": "Ann", "nick": "A, B", "note": "it's", "id": "7"}"#,
+            ),
+        ];
+
+        for (original_str, expected_str) in original_strs {
+            assert_eq!(prepare(Dialect::Java, original_str), expected_str);
+        }
+    }
+
+    #[test]
+    fn java_values_are_cut_at_commas() {
+        let preparer = PrepareOptions::new().dialect(Dialect::Java).build();
+
+        assert_eq!(preparer.prepare("{note=hi, there}"), r#"{"note": "hi"}"#);
+        assert_eq!(
+            preparer.try_prepare("{note=hi, there}").unwrap_err().kind,
+            PrepareErrorKind::OrphanKey
+        );
+    }
+
+    #[test]
+    fn java_type_key() {
+        let json_value = PrepareOptions::new()
+            .dialect(Dialect::Java)
+            .type_key("@type")
+            .build()
+            .prepare_to_value("Device(id=5, owner=Person(name=Ann), tags={a=1})")
+            .unwrap();
+
+        assert_eq!(
+            json_value,
+            json!({
+                "@type": "Device",
+                "id": 5,
+                "owner": {"@type": "Person", "name": "Ann"},
+                "tags": {"a": 1}
+            })
+        );
+    }
+
+    #[test]
+    fn go_values() {
+        let original_strs = [
+            (
+                "{ID:5 Name:pump station Tags:[a b] Owner:<nil>}",
+                r#"{"ID": 5, "Name": "pump station", "Tags": ["a", "b"], "Owner": null}"#,
+            ),
+            ("map[a:1 b:2]", r#"{"a": 1, "b": 2}"#),
+            ("map[1:[x y] 2:map[]]", r#"{"1": ["x", "y"], "2": {}}"#),
+            ("{5 pump [a b]}", r#"[5, "pump", ["a", "b"]]"#),
+            (
+                "&{At:2024-01-02 15:04:05 +0000 UTC Next:&{ID:6}}",
+                r#"{"At": "2024-01-02 15:04:05 +0000 UTC", "Next": {"ID": 6}}"#,
+            ),
+            ("[]", "[]"),
+        ];
+
+        for (original_str, expected_str) in original_strs {
+            assert_eq!(prepare(Dialect::Go, original_str), expected_str);
+        }
+    }
+
+    #[test]
+    fn rust_debug() {
+        let original_strs = [
+            (
+                r#"Device { id: 5, label: Some("pump"), owner: None, tags: ["a", "b"] }"#,
+                r#"{"id": 5, "label": "pump", "owner": null, "tags": ["a", "b"]}"#,
+            ),
+            (
+                r#"[Ok(5), Err("boom"), Ok(())]"#,
+                r#"[{"Ok": 5}, {"Err": "boom"}, {"Ok": null}]"#,
+            ),
+            (
+                r#"(1, 'x', Meters(2.5), Point(1, 2), Parse("bad"))"#,
+                r#"[1, "x", 2.5, [1, 2], "bad"]"#,
+            ),
+            ("vec![1]", "[1]"),
+            ("{1, 2}", "[1, 2]"),
+            (
+                "Reading {\n    value: Some(\n        3,\n    ),\n    state: Idle,\n}",
+                r#"{"value": 3, "state": "Idle"}"#,
+            ),
+        ];
+
+        for (original_str, expected_str) in original_strs {
+            assert_eq!(prepare(Dialect::Rust, original_str), expected_str);
+        }
+    }
+
+    #[test]
+    fn rust_debug_type_key() {
+        let json_value = PrepareOptions::new()
+            .dialect(Dialect::Rust)
+            .type_key("type")
+            .build()
+            .prepare_to_value("Device { id: Id(5), shape: Circle { r: 1 }, last: Some(Ok(2)) }")
+            .unwrap();
+
+        assert_eq!(
+            json_value,
+            json!({
+                "type": "Device",
+                "id": 5,
+                "shape": {"type": "Circle", "r": 1},
+                "last": {"Ok": 2}
+            })
+        );
+    }
+
+    #[test]
+    fn rust_debug_tagged_tuple_variants() {
+        let prepared_str = PrepareOptions::new()
+            .dialect(Dialect::Rust)
+            .tag_tuple_variants(true)
+            .build()
+            .prepare(r#"(Meters(2.5), Point(1, 2), Parse("bad"), Some(Ok(1)))"#);
+
+        assert_eq!(
+            prepared_str,
+            r#"[{"Meters": 2.5}, {"Point": [1, 2]}, {"Parse": "bad"}, {"Ok": 1}]"#
+        );
+    }
+
+    #[test]
+    fn swift_dictionaries() {
+        let original_strs = [
+            (
+                r#"["name": "x", "count": 3, "tags": ["a", "b"], "next": nil]"#,
+                r#"{"name": "x", "count": 3, "tags": ["a", "b"], "next": null}"#,
+            ),
+            ("[:]", "{}"),
+            ("[]", "[]"),
+            (r#"[1, 2, "a:b"]"#, r#"[1, 2, "a:b"]"#),
+            (
+                r#"Device(id: 5, label: Optional("pump"))"#,
+                r#"{"id": 5, "label": "pump"}"#,
+            ),
+            (
+                r#"[AnyHashable("a"): 1, AnyHashable(2): [AnyHashable("b")]]"#,
+                r#"{"a": 1, "2": ["b"]}"#,
+            ),
+        ];
+
+        for (original_str, expected_str) in original_strs {
+            assert_eq!(prepare(Dialect::Swift, original_str), expected_str);
+        }
+    }
+
+    #[test]
+    fn objective_c_descriptions() {
+        let original_str = r#"{
+    count = 3;
+    name = "pump station";
+    owner = "<null>";
+    empty = <null>;
+    tags =     (
+        a,
+        b
+    );
+    nested =     {
+        id = 7;
+    };
+}"#;
+
+        let prepared_str = prepare(Dialect::Swift, original_str);
+
+        let expected_str = r#"{"count": 3, "name": "pump station", "owner": "<null>", "empty": null, "tags": ["a", "b"], "nested": {"id": 7}}"#;
+
+        assert_eq!(prepared_str, expected_str);
+    }
+}